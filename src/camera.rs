@@ -1,6 +1,7 @@
 use rand::Rng;
 
 use crate::{
+    random,
     ray::Ray,
     vec3::{Point3, Vec3},
 };
@@ -72,7 +73,7 @@ impl Camera {
     pub fn ray(&self, u: f64, v: f64) -> Ray {
         let rd = self.lens_radius * Vec3::random_in_unit_disk();
         let offset = self.u * rd.x() + self.v * rd.y();
-        let mut rng = rand::thread_rng();
+        let mut rng = random::rng();
         let time = if self.time0 == self.time1 {
             self.time0
         } else {
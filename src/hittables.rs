@@ -1,25 +1,51 @@
 mod aabb;
+mod animated_transform;
 mod r#box;
 mod bvh_tree;
 mod constant_medium;
+mod csg;
+mod curve;
+mod distance_field;
+mod heightfield;
 mod hit;
 mod hittable;
 mod hittable_vec;
+mod instance;
 mod moving_sphere;
+mod plane;
+mod quad;
+mod quadric;
 mod rect;
 mod rotate;
 mod sphere;
+mod torus;
+mod transform;
 mod translate;
+mod triangle;
+mod triangle_mesh;
 
 pub use aabb::Aabb;
-pub use bvh_tree::BvhTree;
+pub use animated_transform::{AnimatedTransform, Keyframe};
+pub use bvh_tree::{BvhTree, SplitMethod};
 pub use constant_medium::ConstantMedium;
+pub use csg::{Csg, CsgOperation};
+pub use curve::{Curve, CurveShape};
+pub use distance_field::DistanceField;
+pub use heightfield::Heightfield;
 pub use hit::Hit;
 pub use hittable::Hittable;
 pub use hittable_vec::HittableVec;
+pub use instance::Instance;
 pub use moving_sphere::MovingSphere;
+pub use plane::Plane;
+pub use quad::Quad;
+pub use quadric::{Cone, Cylinder, Disk, Paraboloid};
 pub use r#box::Box;
 pub use rect::{XyRect, XzRect, YzRect};
 pub use rotate::RotateY;
 pub use sphere::Sphere;
+pub use torus::Torus;
+pub use transform::Transform;
 pub use translate::Translate;
+pub use triangle::Triangle;
+pub use triangle_mesh::TriangleMesh;
@@ -1,6 +1,10 @@
 use std::mem::swap;
 
-use crate::{ray::Ray, vec3::Point3};
+use crate::{
+    matrix::Matrix4,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[non_exhaustive]
@@ -14,9 +18,46 @@ impl Aabb {
         Aabb { min, max }
     }
 
-    pub fn hit(&self, ray: &Ray, mut t_min: f64, mut t_max: f64) -> bool {
+    pub fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool {
+        let inverse_direction = Vec3::new(
+            1.0 / ray.direction.x(),
+            1.0 / ray.direction.y(),
+            1.0 / ray.direction.z(),
+        );
+        self.hit_with_inverse_direction(ray, inverse_direction, t_min, t_max)
+    }
+
+    /// Same as `hit`, with the reciprocal of the ray direction computed by the caller.
+    pub fn hit_with_inverse_direction(
+        &self,
+        ray: &Ray,
+        inverse_direction: Vec3,
+        t_min: f64,
+        t_max: f64,
+    ) -> bool {
+        self.interval(ray, inverse_direction, t_min, t_max)
+            .is_some()
+    }
+
+    /// The range of `t` within the box and within `t_min..t_max`, if any.
+    pub fn hit_interval(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<(f64, f64)> {
+        let inverse_direction = Vec3::new(
+            1.0 / ray.direction.x(),
+            1.0 / ray.direction.y(),
+            1.0 / ray.direction.z(),
+        );
+        self.interval(ray, inverse_direction, t_min, t_max)
+    }
+
+    fn interval(
+        &self,
+        ray: &Ray,
+        inverse_direction: Vec3,
+        mut t_min: f64,
+        mut t_max: f64,
+    ) -> Option<(f64, f64)> {
         for i in 0..3 {
-            let inv = 1.0 / ray.direction[i];
+            let inv = inverse_direction[i];
             let mut t0 = (self.min[i] - ray.origin[i]) * inv;
             let mut t1 = (self.max[i] - ray.origin[i]) * inv;
             if inv < 0.0 {
@@ -25,10 +66,10 @@ impl Aabb {
             t_min = f64::max(t_min, t0);
             t_max = f64::min(t_max, t1);
             if t_max <= t_min {
-                return false;
+                return None;
             }
         }
-        true
+        Some((t_min, t_max))
     }
 
     pub fn union(&self, other: &Aabb) -> Aabb {
@@ -44,12 +85,50 @@ impl Aabb {
         );
         Aabb::new(min, max)
     }
+
+    /// The overlap of both boxes, which is flat or a point where they are disjoint.
+    pub fn intersection(&self, other: &Aabb) -> Aabb {
+        let min = Point3::new(
+            f64::max(self.min.x(), other.min.x()),
+            f64::max(self.min.y(), other.min.y()),
+            f64::max(self.min.z(), other.min.z()),
+        );
+        let max = Point3::new(
+            f64::max(min.x(), f64::min(self.max.x(), other.max.x())),
+            f64::max(min.y(), f64::min(self.max.y(), other.max.y())),
+            f64::max(min.z(), f64::min(self.max.z(), other.max.z())),
+        );
+        Aabb::new(min, max)
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        let d = self.max - self.min;
+        2.0 * (d.x() * d.y() + d.y() * d.z() + d.z() * d.x())
+    }
+
+    pub fn centroid(&self) -> Point3 {
+        (self.min + self.max) * 0.5
+    }
+
+    /// The smallest box containing this box transformed by `matrix`.
+    pub fn transform(&self, matrix: &Matrix4) -> Aabb {
+        // Jim Arvo's method: each output extent is the sum of the extremes of every input axis
+        let mut min = Point3::new(matrix[0][3], matrix[1][3], matrix[2][3]);
+        let mut max = min;
+        for i in 0..3 {
+            for j in 0..3 {
+                let a = matrix[i][j] * self.min[j];
+                let b = matrix[i][j] * self.max[j];
+                min[i] += a.min(b);
+                max[i] += a.max(b);
+            }
+        }
+        Aabb::new(min, max)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::vec3::Vec3;
-
     use super::*;
 
     #[test]
@@ -89,4 +168,27 @@ mod tests {
             aabb1.union(&aabb2)
         )
     }
+
+    #[test]
+    fn test_surface_area() {
+        let aabb = Aabb::new(Point3::new(-1.0, 0.0, 0.0), Point3::new(1.0, 3.0, 4.0));
+
+        assert_eq!(
+            2.0 * (2.0 * 3.0 + 3.0 * 4.0 + 4.0 * 2.0),
+            aabb.surface_area()
+        );
+        assert_eq!(Point3::new(0.0, 1.5, 2.0), aabb.centroid());
+    }
+
+    #[test]
+    fn test_transform() {
+        let aabb = Aabb::new(Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 1.0, 1.0));
+        let matrix = Matrix4::translation(Vec3::new(1.0, 2.0, 3.0))
+            * Matrix4::rotation(Vec3::new(0.0, 0.0, 1.0), std::f64::consts::FRAC_PI_4);
+        let transformed = aabb.transform(&matrix);
+
+        let sqrt2 = 2.0f64.sqrt();
+        assert!((transformed.min - Point3::new(1.0 - sqrt2, 2.0 - sqrt2, 2.0)).length() < 1e-10);
+        assert!((transformed.max - Point3::new(1.0 + sqrt2, 2.0 + sqrt2, 4.0)).length() < 1e-10);
+    }
 }
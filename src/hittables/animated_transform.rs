@@ -0,0 +1,320 @@
+use crate::{
+    matrix::Matrix4,
+    quaternion::Quaternion,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{Aabb, Hit, Hittable};
+
+/// Pose of an `AnimatedTransform` at a point in time.
+///
+/// The object is scaled first, then rotated, then translated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Keyframe {
+    pub time: f64,
+    pub translation: Vec3,
+    pub rotation: Quaternion,
+    pub scale: Vec3,
+}
+
+impl Keyframe {
+    pub fn new(time: f64, translation: Vec3, rotation: Quaternion, scale: Vec3) -> Keyframe {
+        Keyframe {
+            time,
+            translation,
+            rotation,
+            scale,
+        }
+    }
+}
+
+/// Transform interpolated between keyframes by the time of each ray, for motion blur.
+///
+/// Translation and scale are interpolated linearly and rotation by slerp. Before the first
+/// keyframe and after the last one the object stays still.
+#[derive(Debug, Clone)]
+pub struct AnimatedTransform<H: Hittable> {
+    hittable: H,
+    keyframes: Vec<Keyframe>,
+}
+
+/// Number of steps per keyframe interval sampled to bound the motion
+const BOUNDING_STEPS: usize = 16;
+
+impl<H: Hittable> AnimatedTransform<H> {
+    /// Panics if there are no keyframes, a scale has a zero component, or a component changes
+    /// sign between keyframes, which would make the scale zero in between.
+    pub fn new(hittable: H, mut keyframes: Vec<Keyframe>) -> AnimatedTransform<H> {
+        assert!(!keyframes.is_empty(), "at least one keyframe is required");
+        assert!(
+            keyframes
+                .iter()
+                .all(|keyframe| (0..3).all(|i| keyframe.scale[i] != 0.0)),
+            "the scale must not be zero"
+        );
+        let first = keyframes[0].scale;
+        assert!(
+            keyframes
+                .iter()
+                .all(|keyframe| (0..3).all(|i| (keyframe.scale[i] < 0.0) == (first[i] < 0.0))),
+            "the scale must not change sign"
+        );
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        AnimatedTransform {
+            hittable,
+            keyframes,
+        }
+    }
+
+    /// The keyframes around `time` and the interpolation parameter between them.
+    fn segment(&self, time: f64) -> (&Keyframe, &Keyframe, f64) {
+        let i = self
+            .keyframes
+            .partition_point(|keyframe| keyframe.time <= time);
+        if i == 0 {
+            let first = &self.keyframes[0];
+            return (first, first, 0.0);
+        }
+        if i == self.keyframes.len() {
+            let last = &self.keyframes[i - 1];
+            return (last, last, 0.0);
+        }
+        let (k0, k1) = (&self.keyframes[i - 1], &self.keyframes[i]);
+        (k0, k1, (time - k0.time) / (k1.time - k0.time))
+    }
+
+    fn pose(&self, time: f64) -> (Vec3, Quaternion, Vec3) {
+        let (k0, k1, t) = self.segment(time);
+        (
+            k0.translation + (k1.translation - k0.translation) * t,
+            k0.rotation.slerp(k1.rotation, t),
+            k0.scale + (k1.scale - k0.scale) * t,
+        )
+    }
+
+    fn to_world(&self, time: f64) -> Matrix4 {
+        let (translation, rotation, scale) = self.pose(time);
+        Matrix4::translation(translation) * rotation.to_matrix() * Matrix4::scaling(scale)
+    }
+
+    /// Bounds how far any point of the object within `radius` of its origin strays between
+    /// `start` and `end` from the straight line between its positions at those times.
+    ///
+    /// Translation is linear, so only rotation and scaling curve the path `p(s) = R(s) S(s) x`.
+    /// With `S` linear and `R` turning by `angle`,
+    /// `|p''| <= radius * (angle^2 * (|S| + |dS|) + 2 * angle * |dS|)`,
+    /// and a curve deviates from its chord by at most an eighth of that.
+    fn chord_deviation(&self, start: f64, end: f64, radius: f64) -> f64 {
+        let (_, rotation0, scale0) = self.pose(start);
+        let (_, rotation1, scale1) = self.pose(end);
+        let angle = rotation0.angle_to(rotation1);
+        let max_abs = |v: Vec3| v.x().abs().max(v.y().abs()).max(v.z().abs());
+        let scale = max_abs(scale0);
+        let scale_change = max_abs(scale1 - scale0);
+        radius * (angle * angle * (scale + scale_change) + 2.0 * angle * scale_change) / 8.0
+    }
+}
+
+impl<H: Hittable> Hittable for AnimatedTransform<H> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
+        let (translation, rotation, scale) = self.pose(ray.time);
+        let rotation = rotation.to_matrix();
+        let inverse_scale =
+            Matrix4::scaling(Vec3::new(1.0 / scale.x(), 1.0 / scale.y(), 1.0 / scale.z()));
+        let to_object = inverse_scale * rotation.transpose() * Matrix4::translation(-translation);
+
+        // The direction is not normalized, so that `t` is the same in both spaces
+        let object_ray = Ray::new(
+            to_object.transform_point(ray.origin),
+            to_object.transform_vector(ray.direction),
+            ray.time,
+        );
+        let mut hit = self.hittable.hit(&object_ray, t_min, t_max)?;
+        hit.p = translation + rotation.transform_vector(scale * hit.p);
+        // The inverse transpose of the rotation and scaling
+        hit.normal = rotation
+            .transform_vector(inverse_scale.transform_vector(hit.normal))
+            .unit();
+        Some(hit)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        let aabb = self.hittable.bounding_box(time0, time1)?;
+        // The farthest distance of the object from its origin
+        let radius = (0..8)
+            .map(|corner| {
+                let pick = |axis: usize| {
+                    if corner & (1 << axis) == 0 {
+                        aabb.min[axis]
+                    } else {
+                        aabb.max[axis]
+                    }
+                };
+                Point3::new(pick(0), pick(1), pick(2)).length()
+            })
+            .fold(0.0, f64::max);
+
+        // The keyframes split the interval into pieces where the motion is smooth
+        let mut times = vec![time0];
+        times.extend(
+            self.keyframes
+                .iter()
+                .map(|keyframe| keyframe.time)
+                .filter(|&time| time0 < time && time < time1),
+        );
+        times.push(time1);
+
+        let mut bbox = aabb.transform(&self.to_world(time0));
+        let mut padding = 0.0;
+        for pair in times.windows(2) {
+            let step = (pair[1] - pair[0]) / BOUNDING_STEPS as f64;
+            for i in 1..=BOUNDING_STEPS {
+                let (start, end) = (pair[0] + step * (i - 1) as f64, pair[0] + step * i as f64);
+                bbox = bbox.union(&aabb.transform(&self.to_world(end)));
+                padding = f64::max(padding, self.chord_deviation(start, end, radius));
+            }
+        }
+
+        let padding = Vec3::new(padding, padding, padding);
+        Some(Aabb::new(bbox.min - padding, bbox.max + padding))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::{
+        hittables::{Box as HittableBox, Sphere},
+        materials::Lambertian,
+        vec3::Color,
+    };
+
+    use super::*;
+
+    fn still(time: f64, translation: Vec3) -> Keyframe {
+        Keyframe::new(
+            time,
+            translation,
+            Quaternion::identity(),
+            Vec3::new(1.0, 1.0, 1.0),
+        )
+    }
+
+    #[test]
+    fn test_interpolation() {
+        let sphere = Sphere::new(
+            Point3::default(),
+            1.0,
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        );
+        let animated = AnimatedTransform::new(
+            sphere,
+            vec![
+                still(1.0, Vec3::new(4.0, 0.0, 0.0)),
+                still(0.0, Vec3::new(0.0, 0.0, 0.0)),
+            ],
+        );
+        let hit = |x: f64, time: f64| {
+            let ray = Ray::new(Point3::new(x, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0), time);
+            animated.hit(&ray, 0.001, f64::INFINITY).map(|hit| hit.p)
+        };
+
+        assert!((hit(0.0, 0.0).unwrap() - Point3::new(0.0, 0.0, -1.0)).length() < 1e-10);
+        assert!(hit(0.0, 1.0).is_none());
+        assert!((hit(2.0, 0.5).unwrap() - Point3::new(2.0, 0.0, -1.0)).length() < 1e-10);
+        // Still after the last keyframe
+        assert!((hit(4.0, 2.0).unwrap() - Point3::new(4.0, 0.0, -1.0)).length() < 1e-10);
+    }
+
+    #[test]
+    fn test_rotation_and_scale() {
+        let unit_box = HittableBox::new(
+            Point3::new(-1.0, -1.0, -1.0),
+            Point3::new(1.0, 1.0, 1.0),
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        );
+        let axis = Vec3::new(0.0, 1.0, 0.0);
+        let animated = AnimatedTransform::new(
+            unit_box,
+            vec![
+                Keyframe::new(
+                    0.0,
+                    Vec3::default(),
+                    Quaternion::identity(),
+                    Vec3::new(2.0, 1.0, 1.0),
+                ),
+                Keyframe::new(
+                    1.0,
+                    Vec3::default(),
+                    Quaternion::from_axis_angle(axis, PI / 2.0),
+                    Vec3::new(2.0, 1.0, 1.0),
+                ),
+            ],
+        );
+
+        // At the end the box is stretched along z instead of x
+        let ray = Ray::new(Point3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0), 1.0);
+        let hit = animated.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 3.0).abs() < 1e-10);
+        assert!((hit.normal - Vec3::new(0.0, 0.0, -1.0)).length() < 1e-10);
+
+        // Halfway the normal of the front face is turned by 45 degrees
+        let ray = Ray::new(Point3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0), 0.5);
+        let hit = animated.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!(
+            hit.normal.z() < 0.0 && (hit.normal.x().abs() - hit.normal.z().abs()).abs() < 1e-10
+        );
+    }
+
+    #[test]
+    fn test_bounding_box_encloses_motion() {
+        let unit_box = HittableBox::new(
+            Point3::new(-1.0, -1.0, -1.0),
+            Point3::new(1.0, 1.0, 1.0),
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        );
+        let axis = Vec3::new(1.0, 2.0, 3.0);
+        let animated = AnimatedTransform::new(
+            unit_box,
+            vec![
+                Keyframe::new(
+                    0.0,
+                    Vec3::new(-1.0, 0.0, 0.0),
+                    Quaternion::identity(),
+                    Vec3::new(1.0, 1.0, 1.0),
+                ),
+                Keyframe::new(
+                    0.5,
+                    Vec3::new(0.0, 2.0, 0.0),
+                    Quaternion::from_axis_angle(axis, 2.0),
+                    Vec3::new(3.0, 0.5, 1.0),
+                ),
+                Keyframe::new(
+                    1.0,
+                    Vec3::new(1.0, 0.0, 0.0),
+                    Quaternion::from_axis_angle(axis, -1.0),
+                    Vec3::new(1.0, 1.0, 2.0),
+                ),
+            ],
+        );
+        let bbox = animated.bounding_box(0.0, 1.0).unwrap();
+
+        // Every corner of the box stays inside at all times
+        for i in 0..=1000 {
+            let to_world = animated.to_world(i as f64 / 1000.0);
+            for corner in 0..8 {
+                let pick = |axis: usize| if corner & (1 << axis) == 0 { -1.0 } else { 1.0 };
+                let p = to_world.transform_point(Point3::new(pick(0), pick(1), pick(2)));
+                for axis in 0..3 {
+                    assert!(bbox.min[axis] <= p[axis] && p[axis] <= bbox.max[axis]);
+                }
+            }
+        }
+
+        // A part of the motion is enclosed by a smaller box
+        let first_half = animated.bounding_box(0.0, 0.25).unwrap();
+        assert!(first_half.surface_area() < bbox.surface_area());
+    }
+}
@@ -2,15 +2,18 @@ use crate::{materials::Material, ray::Ray, vec3::Point3};
 
 use super::{Aabb, Hit, Hittable, HittableVec, XyRect, XzRect, YzRect};
 
-pub struct Box<M: Material + Clone> {
+pub struct Box {
     box_min: Point3,
     box_max: Point3,
     sides: HittableVec,
-    material: M,
 }
 
-impl<M: Material + Clone + 'static> Box<M> {
-    pub fn new(box_min: Point3, box_max: Point3, material: M) -> Box<M> {
+impl Box {
+    pub fn new<M: Material + Clone + 'static>(
+        box_min: Point3,
+        box_max: Point3,
+        material: M,
+    ) -> Box {
         let sides: HittableVec = vec![
             std::boxed::Box::new(XyRect::new(
                 box_min.x(),
@@ -58,7 +61,7 @@ impl<M: Material + Clone + 'static> Box<M> {
                 box_min.z(),
                 box_max.z(),
                 box_min.x(),
-                material.clone(),
+                material,
             )),
         ];
 
@@ -66,17 +69,56 @@ impl<M: Material + Clone + 'static> Box<M> {
             box_min,
             box_max,
             sides,
-            material,
         }
     }
 }
 
-impl<M: Material + Clone> Hittable for Box<M> {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit> {
-        self.sides.hit(ray, t_min, t_max)
+impl Hittable for Box {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
+        let mut hit = self.sides.hit(ray, t_min, t_max)?;
+        // The sides face the positive axes, so the normals of the sides at `box_min` point inward
+        let center = (self.box_min + self.box_max) / 2.0;
+        let outward_normal = if hit.normal.dot(hit.p - center) < 0.0 {
+            -hit.normal
+        } else {
+            hit.normal
+        };
+        hit.set_face_normal(ray, outward_normal);
+        Some(hit)
     }
 
     fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
         Some(Aabb::new(self.box_min, self.box_max))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{materials::Lambertian, vec3::Vec3};
+
+    use super::*;
+
+    #[test]
+    fn test_front_face() {
+        let unit_box = Box::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 1.0),
+            Lambertian::new_with_color(Vec3::new(0.5, 0.5, 0.5)),
+        );
+
+        for direction in [1.0, -1.0] {
+            // From outside into the side at either end, then out of the other side
+            let ray = Ray::new(
+                Point3::new(0.5 - 2.0 * direction, 0.5, 0.5),
+                Vec3::new(direction, 0.0, 0.0),
+                0.0,
+            );
+            let hit = unit_box.hit(&ray, 0.001, f64::INFINITY).unwrap();
+            assert!(hit.front_face);
+            assert_eq!(-direction, hit.normal.x());
+            let hit = unit_box.hit(&ray, hit.t + 0.001, f64::INFINITY).unwrap();
+            assert!(!hit.front_face);
+            assert_eq!(-direction, hit.normal.x());
+        }
+    }
+}
@@ -1,110 +1,663 @@
 use std::cmp::Ordering;
 
 use rand::Rng;
+#[cfg(feature = "parallel")]
+use rayon::prelude::{IntoParallelIterator, ParallelIterator, ParallelSlice, ParallelSliceMut};
 
-use crate::ray::Ray;
+use crate::{
+    random,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
 
 use super::{Aabb, Hit, Hittable, HittableVec};
 
-#[non_exhaustive]
-pub enum BvhTree {
+/// Number of bins along an axis to evaluate the surface area heuristic
+const BIN_COUNT: usize = 12;
+/// Nodes with more primitives than this are always split
+const MAX_LEAF_SIZE: usize = 4;
+/// Cost of traversing a node relative to intersecting a primitive
+const TRAVERSAL_COST: f64 = 0.125;
+/// Beyond this depth SAH splits give way to halving, which bounds the traversal stack
+const MAX_SAH_DEPTH: usize = 64;
+const STACK_SIZE: usize = 128;
+/// Nodes with at least this many primitives are built in parallel with the `parallel` feature
+const PARALLEL_THRESHOLD: usize = 4096;
+
+/// How to split the primitives of a node into two children.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SplitMethod {
+    /// Binned surface area heuristic; leaves may hold up to a few primitives
+    #[default]
+    Sah,
+    /// Median of a random axis, one primitive per leaf
+    Median,
+}
+
+/// Bounding volume hierarchy stored as a flat array of nodes in depth-first order.
+///
+/// Objects without a bounding box, such as infinite planes, are kept aside and tested against
+/// every ray.
+pub struct BvhTree {
+    nodes: Vec<LinearNode>,
+    /// Ordered so that the primitives of every leaf are contiguous
+    hittables: HittableVec,
+    unbounded: HittableVec,
+}
+
+#[derive(Debug, PartialEq)]
+struct LinearNode {
+    bounding_box: Aabb,
+    kind: LinearNodeKind,
+}
+
+#[derive(Debug, PartialEq)]
+enum LinearNodeKind {
+    Leaf {
+        first: u32,
+        count: u32,
+    },
+    /// The first child follows its parent immediately
+    Interior {
+        second_child: u32,
+        axis: u8,
+    },
+}
+
+/// Pointer-based tree built first and then flattened
+enum BuildNode {
     Leaf {
         bounding_box: Aabb,
-        hittable: Box<dyn Hittable>,
+        hittables: HittableVec,
     },
-    Node {
+    Interior {
         bounding_box: Aabb,
-        left: Box<BvhTree>,
-        right: Box<BvhTree>,
+        axis: usize,
+        left: Box<BuildNode>,
+        right: Box<BuildNode>,
     },
 }
 
+struct Primitive {
+    bounding_box: Aabb,
+    centroid: Point3,
+    hittable: Box<dyn Hittable>,
+}
+
 impl BvhTree {
-    fn new_leaf(hittable: Box<dyn Hittable>, time0: f64, time1: f64) -> BvhTree {
-        let Some(bounding_box) = hittable.bounding_box(time0, time1) else {
-            panic!("No bounding box")
+    pub fn new(hittables: HittableVec, time0: f64, time1: f64) -> BvhTree {
+        BvhTree::new_with_split_method(hittables, time0, time1, SplitMethod::default())
+    }
+
+    pub fn new_with_split_method(
+        hittables: HittableVec,
+        time0: f64,
+        time1: f64,
+        split_method: SplitMethod,
+    ) -> BvhTree {
+        let builder = Builder {
+            parallel_threshold: PARALLEL_THRESHOLD,
         };
-        BvhTree::Leaf {
-            bounding_box,
-            hittable,
-        }
+        builder.build(hittables, time0, time1, split_method)
     }
 
-    pub fn new(mut hittables: HittableVec, time0: f64, time1: f64) -> BvhTree {
-        let left: BvhTree;
-        let right: BvhTree;
-        let mut rng = rand::thread_rng();
-        let axis: usize = rng.gen_range(0..3);
-        match hittables.len() {
-            1 => {
-                return BvhTree::new_leaf(hittables.pop().unwrap(), time0, time1);
+    /// Appends the subtree in depth-first order and returns the index of its root.
+    fn flatten(&mut self, node: BuildNode) -> u32 {
+        let index = self.nodes.len() as u32;
+        match node {
+            BuildNode::Leaf {
+                bounding_box,
+                hittables,
+            } => {
+                self.nodes.push(LinearNode {
+                    bounding_box,
+                    kind: LinearNodeKind::Leaf {
+                        first: self.hittables.len() as u32,
+                        count: hittables.len() as u32,
+                    },
+                });
+                self.hittables.extend(hittables);
             }
-            2 => {
-                let r = hittables.pop().unwrap();
-                let l = hittables.pop().unwrap();
-                if Self::compare_box(l.as_ref(), r.as_ref(), axis).is_gt() {
-                    left = BvhTree::new_leaf(l, time0, time1);
-                    right = BvhTree::new_leaf(r, time0, time1);
-                } else {
-                    left = BvhTree::new_leaf(r, time0, time1);
-                    right = BvhTree::new_leaf(l, time0, time1);
+            BuildNode::Interior {
+                bounding_box,
+                axis,
+                left,
+                right,
+            } => {
+                self.nodes.push(LinearNode {
+                    bounding_box,
+                    kind: LinearNodeKind::Interior {
+                        second_child: 0,
+                        axis: axis as u8,
+                    },
+                });
+                self.flatten(*left);
+                let second = self.flatten(*right);
+                if let LinearNodeKind::Interior { second_child, .. } =
+                    &mut self.nodes[index as usize].kind
+                {
+                    *second_child = second;
                 }
             }
-            _ => {
-                hittables.sort_by(|a, b| Self::compare_box(a.as_ref(), b.as_ref(), axis));
-                let hittables_right = hittables.split_off(hittables.len() / 2);
-                left = BvhTree::new(hittables, time0, time1);
-                right = BvhTree::new(hittables_right, time0, time1);
-            }
         }
+        index
+    }
+}
 
-        let box_left = left.bounding_box(time0, time1);
-        let box_right = right.bounding_box(time0, time1);
-        // FIXME: 一方だけでもあればそれでよいのでは？
-        let Some(bounding_box) = box_left.and_then(|box_left| box_right.map(|box_right| box_right.union(&box_left))) else {
-            panic!("No bounding box");
-        };
+impl BuildNode {
+    fn new_leaf(primitives: Vec<Primitive>) -> BuildNode {
+        BuildNode::Leaf {
+            bounding_box: bounding_box(&primitives, |p| p.bounding_box),
+            hittables: primitives.into_iter().map(|p| p.hittable).collect(),
+        }
+    }
 
-        BvhTree::Node {
-            bounding_box,
+    fn new_interior(axis: usize, left: BuildNode, right: BuildNode) -> BuildNode {
+        BuildNode::Interior {
+            bounding_box: left.bounding_box().union(&right.bounding_box()),
+            axis,
             left: Box::new(left),
             right: Box::new(right),
         }
     }
 
-    fn compare_box(a: &dyn Hittable, b: &dyn Hittable, axis: usize) -> Ordering {
-        let box_a = a.bounding_box(0.0, 0.0);
-        let box_b = b.bounding_box(0.0, 0.0);
-        let (Some(box_a),Some(box_b)) = (box_a,box_b) else {
-            panic!("No bounding box");
+    fn bounding_box(&self) -> Aabb {
+        match self {
+            BuildNode::Leaf { bounding_box, .. } => *bounding_box,
+            BuildNode::Interior { bounding_box, .. } => *bounding_box,
+        }
+    }
+}
+
+/// Builds the tree with the nodes of at least `parallel_threshold` primitives in parallel.
+///
+/// The result does not depend on the threshold; serial builds use `usize::MAX`.
+struct Builder {
+    parallel_threshold: usize,
+}
+
+impl Builder {
+    fn build(
+        &self,
+        hittables: HittableVec,
+        time0: f64,
+        time1: f64,
+        split_method: SplitMethod,
+    ) -> BvhTree {
+        assert!(!hittables.is_empty(), "No hittables");
+        let (primitives, unbounded) = self.primitives(hittables, time0, time1);
+        let count = primitives.len();
+        let mut tree = BvhTree {
+            nodes: Vec::new(),
+            hittables: Vec::with_capacity(count),
+            unbounded,
+        };
+        if count == 0 {
+            return tree;
+        }
+
+        let root = match split_method {
+            SplitMethod::Sah => self.sah(primitives, 0),
+            SplitMethod::Median => {
+                // Axes are drawn up front in the order of a depth-first build, so that subtrees
+                // built on other threads use the same random numbers as a serial build
+                let mut rng = random::rng();
+                let axes = (1..count).map(|_| rng.gen_range(0..3)).collect::<Vec<_>>();
+                self.median(primitives, &axes)
+            }
+        };
+        tree.flatten(root);
+        tree
+    }
+
+    /// Splits the hittables into primitives with bounding boxes and those without.
+    fn primitives(
+        &self,
+        hittables: HittableVec,
+        time0: f64,
+        time1: f64,
+    ) -> (Vec<Primitive>, HittableVec) {
+        let mut primitives = Vec::with_capacity(hittables.len());
+        let mut unbounded = Vec::new();
+        for result in self.bounding_boxes(hittables, time0, time1) {
+            match result {
+                Ok(primitive) => primitives.push(primitive),
+                Err(hittable) => unbounded.push(hittable),
+            }
+        }
+        (primitives, unbounded)
+    }
+
+    fn bounding_boxes(
+        &self,
+        hittables: HittableVec,
+        time0: f64,
+        time1: f64,
+    ) -> Vec<Result<Primitive, Box<dyn Hittable>>> {
+        let primitive = |hittable: Box<dyn Hittable>| match hittable.bounding_box(time0, time1) {
+            Some(bounding_box) => Ok(Primitive {
+                bounding_box,
+                centroid: bounding_box.centroid(),
+                hittable,
+            }),
+            None => Err(hittable),
+        };
+        if self.is_parallel(hittables.len()) {
+            #[cfg(feature = "parallel")]
+            return hittables.into_par_iter().map(primitive).collect();
+        }
+        hittables.into_iter().map(primitive).collect()
+    }
+
+    fn is_parallel(&self, count: usize) -> bool {
+        cfg!(feature = "parallel") && count >= self.parallel_threshold
+    }
+
+    /// Builds both subtrees, concurrently for large nodes.
+    fn join(
+        &self,
+        count: usize,
+        left: impl FnOnce() -> BuildNode + Send,
+        right: impl FnOnce() -> BuildNode + Send,
+    ) -> (BuildNode, BuildNode) {
+        if self.is_parallel(count) {
+            #[cfg(feature = "parallel")]
+            return rayon::join(left, right);
+        }
+        (left(), right())
+    }
+
+    /// `axes` holds the split axis of every interior node of the subtree in depth-first order.
+    fn median(&self, mut primitives: Vec<Primitive>, axes: &[usize]) -> BuildNode {
+        let count = primitives.len();
+        if count == 1 {
+            return BuildNode::new_leaf(primitives);
+        }
+
+        let axis = axes[0];
+        let compare = |a: &Primitive, b: &Primitive| {
+            a.bounding_box.min[axis].total_cmp(&b.bounding_box.min[axis])
+        };
+        self.sort(&mut primitives, compare);
+        let right = primitives.split_off(count / 2);
+        let (axes_left, axes_right) = axes[1..].split_at(primitives.len() - 1);
+        let (left, right) = self.join(
+            count,
+            || self.median(primitives, axes_left),
+            || self.median(right, axes_right),
+        );
+        BuildNode::new_interior(axis, left, right)
+    }
+
+    fn sah(&self, mut primitives: Vec<Primitive>, depth: usize) -> BuildNode {
+        let count = primitives.len();
+        if count == 1 {
+            return BuildNode::new_leaf(primitives);
+        }
+
+        let centroid_box = bounding_box(&primitives, |p| Aabb::new(p.centroid, p.centroid));
+        let split = match self.best_split(&primitives, &centroid_box) {
+            Some(split) if depth < MAX_SAH_DEPTH => split,
+            _ => {
+                // All centroids coincide, so no split separates them, or the tree is too deep
+                if count <= MAX_LEAF_SIZE {
+                    return BuildNode::new_leaf(primitives);
+                }
+                let axis = longest_axis(&centroid_box);
+                self.sort(&mut primitives, |a, b| {
+                    a.centroid[axis].total_cmp(&b.centroid[axis])
+                });
+                let right = primitives.split_off(count / 2);
+                let (left, right) = self.join(
+                    count,
+                    || self.sah(primitives, depth + 1),
+                    || self.sah(right, depth + 1),
+                );
+                return BuildNode::new_interior(axis, left, right);
+            }
+        };
+
+        // Costs are scaled by the surface area of the node to avoid dividing by a zero area
+        let leaf_cost = count as f64 * bounding_box(&primitives, |p| p.bounding_box).surface_area();
+        if count <= MAX_LEAF_SIZE && leaf_cost <= split.cost {
+            return BuildNode::new_leaf(primitives);
+        }
+
+        let (left, right): (Vec<_>, Vec<_>) = primitives
+            .into_iter()
+            .partition(|p| bin_index(p, &centroid_box, split.axis) <= split.bin);
+        let (left, right) = self.join(
+            count,
+            || self.sah(left, depth + 1),
+            || self.sah(right, depth + 1),
+        );
+        BuildNode::new_interior(split.axis, left, right)
+    }
+
+    /// Stable sort, so that the order does not depend on the thread count
+    fn sort(
+        &self,
+        primitives: &mut [Primitive],
+        compare: impl Fn(&Primitive, &Primitive) -> Ordering + Sync,
+    ) {
+        if self.is_parallel(primitives.len()) {
+            #[cfg(feature = "parallel")]
+            return primitives.par_sort_by(compare);
+        }
+        primitives.sort_by(compare);
+    }
+
+    /// Finds the split between bins with the lowest SAH cost over all axes.
+    fn best_split(&self, primitives: &[Primitive], centroid_box: &Aabb) -> Option<Split> {
+        let node_area = bounding_box(primitives, |p| p.bounding_box).surface_area();
+        let mut best: Option<Split> = None;
+        for axis in 0..3 {
+            if centroid_box.max[axis] <= centroid_box.min[axis] {
+                continue;
+            }
+
+            let Bins { counts, boxes } = self.bins(primitives, centroid_box, axis);
+
+            // Sweep from the right to get the area and count of the right side of every split
+            let mut right_costs = [0.0; BIN_COUNT];
+            let (mut right_box, mut right_count) = (None, 0);
+            for bin in (1..BIN_COUNT).rev() {
+                right_box = boxes[bin].map(|b| union(right_box, b)).or(right_box);
+                right_count += counts[bin];
+                right_costs[bin - 1] =
+                    right_box.map_or(0.0, |b| b.surface_area()) * right_count as f64;
+            }
+
+            let (mut left_box, mut left_count) = (None, 0);
+            for bin in 0..BIN_COUNT - 1 {
+                left_box = boxes[bin].map(|b| union(left_box, b)).or(left_box);
+                left_count += counts[bin];
+                if left_count == 0 || left_count == primitives.len() {
+                    continue;
+                }
+                let left_cost = left_box.map_or(0.0, |b| b.surface_area()) * left_count as f64;
+                let cost = TRAVERSAL_COST * node_area + left_cost + right_costs[bin];
+                if best.as_ref().is_none_or(|best| cost < best.cost) {
+                    best = Some(Split { axis, bin, cost });
+                }
+            }
+        }
+        best
+    }
+
+    /// Bins the primitives by their centroids, in parallel chunks for large nodes.
+    ///
+    /// Merging bins only adds counts and takes minima and maxima, so the result is exact.
+    fn bins(&self, primitives: &[Primitive], centroid_box: &Aabb, axis: usize) -> Bins {
+        let bins = |primitives: &[Primitive]| {
+            let mut bins = Bins::default();
+            for p in primitives {
+                let bin = bin_index(p, centroid_box, axis);
+                bins.counts[bin] += 1;
+                bins.boxes[bin] = Some(union(bins.boxes[bin], p.bounding_box));
+            }
+            bins
         };
+        if self.is_parallel(primitives.len()) {
+            #[cfg(feature = "parallel")]
+            return primitives
+                .par_chunks(PARALLEL_THRESHOLD / 4)
+                .map(bins)
+                .reduce(Bins::default, Bins::merge);
+        }
+        bins(primitives)
+    }
+}
 
-        box_a.min[axis].total_cmp(&box_b.min[axis])
+#[derive(Default)]
+struct Bins {
+    counts: [usize; BIN_COUNT],
+    boxes: [Option<Aabb>; BIN_COUNT],
+}
+
+impl Bins {
+    #[cfg(feature = "parallel")]
+    fn merge(mut self, other: Bins) -> Bins {
+        for bin in 0..BIN_COUNT {
+            self.counts[bin] += other.counts[bin];
+            self.boxes[bin] = match (self.boxes[bin], other.boxes[bin]) {
+                (Some(a), b) => Some(union(b, a)),
+                (None, b) => b,
+            };
+        }
+        self
     }
 }
 
-impl Hittable for BvhTree {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit> {
-        if !self.bounding_box(0.0, 0.0).unwrap().hit(ray, t_min, t_max) {
+struct Split {
+    axis: usize,
+    /// Primitives in this bin or below go to the left child
+    bin: usize,
+    cost: f64,
+}
+
+fn bin_index(primitive: &Primitive, centroid_box: &Aabb, axis: usize) -> usize {
+    let extent = centroid_box.max[axis] - centroid_box.min[axis];
+    let offset = (primitive.centroid[axis] - centroid_box.min[axis]) / extent;
+    ((offset * BIN_COUNT as f64) as usize).min(BIN_COUNT - 1)
+}
+
+fn bounding_box(primitives: &[Primitive], f: impl Fn(&Primitive) -> Aabb) -> Aabb {
+    primitives.iter().map(f).reduce(|a, b| a.union(&b)).unwrap()
+}
+
+fn longest_axis(aabb: &Aabb) -> usize {
+    let d = aabb.max - aabb.min;
+    if d.x() >= d.y() && d.x() >= d.z() {
+        0
+    } else if d.y() >= d.z() {
+        1
+    } else {
+        2
+    }
+}
+
+fn union(a: Option<Aabb>, b: Aabb) -> Aabb {
+    a.map_or(b, |a| a.union(&b))
+}
+
+impl BvhTree {
+    /// The closest hit among the bounded primitives.
+    fn traverse(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
+        if self.nodes.is_empty() {
             return None;
         }
-
-        match self {
-            BvhTree::Leaf { hittable, .. } => hittable.hit(ray, t_min, t_max),
-            BvhTree::Node { left, right, .. } => {
-                if let Some(hit_left) = left.hit(ray, t_min, t_max) {
-                    right.hit(ray, t_min, hit_left.t).or(Some(hit_left))
-                } else {
-                    right.hit(ray, t_min, t_max)
+        let inverse_direction = Vec3::new(
+            1.0 / ray.direction.x(),
+            1.0 / ray.direction.y(),
+            1.0 / ray.direction.z(),
+        );
+        let mut closest: Option<Hit> = None;
+        let mut t_max = t_max;
+        let mut stack = [0u32; STACK_SIZE];
+        let mut stack_size = 0;
+        let mut index = 0;
+        loop {
+            let node = &self.nodes[index as usize];
+            if node
+                .bounding_box
+                .hit_with_inverse_direction(ray, inverse_direction, t_min, t_max)
+            {
+                match node.kind {
+                    LinearNodeKind::Leaf { first, count } => {
+                        let hittables = &self.hittables[first as usize..(first + count) as usize];
+                        for hittable in hittables {
+                            if let Some(hit) = hittable.hit(ray, t_min, t_max) {
+                                t_max = hit.t;
+                                closest = Some(hit);
+                            }
+                        }
+                    }
+                    LinearNodeKind::Interior { second_child, axis } => {
+                        // Visit the child nearer to the ray origin first, so that the far one is
+                        // more likely to be culled by a closer hit
+                        let (near, far) = if inverse_direction[axis as usize] < 0.0 {
+                            (second_child, index + 1)
+                        } else {
+                            (index + 1, second_child)
+                        };
+                        stack[stack_size] = far;
+                        stack_size += 1;
+                        index = near;
+                        continue;
+                    }
                 }
             }
+            if stack_size == 0 {
+                break;
+            }
+            stack_size -= 1;
+            index = stack[stack_size];
         }
+        closest
+    }
+}
+
+impl Hittable for BvhTree {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
+        let closest = self.traverse(ray, t_min, t_max);
+        if self.unbounded.is_empty() {
+            return closest;
+        }
+        let t_max = closest.as_ref().map_or(t_max, |hit| hit.t);
+        self.unbounded.hit(ray, t_min, t_max).or(closest)
     }
 
     fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
-        Some(match self {
-            BvhTree::Leaf { bounding_box, .. } => *bounding_box,
-            BvhTree::Node { bounding_box, .. } => *bounding_box,
-        })
+        if !self.unbounded.is_empty() {
+            return None;
+        }
+        Some(self.nodes[0].bounding_box)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{hittables::Sphere, materials::Lambertian};
+
+    use super::*;
+
+    fn spheres(centers: impl IntoIterator<Item = Point3>) -> HittableVec {
+        centers
+            .into_iter()
+            .map(|center| {
+                Box::new(Sphere::new(
+                    center,
+                    0.5,
+                    Lambertian::new_with_color(Vec3::new(0.5, 0.5, 0.5)),
+                )) as Box<dyn Hittable>
+            })
+            .collect()
+    }
+
+    fn grid() -> Vec<Point3> {
+        (0..200)
+            .map(|i| Point3::new((i % 7) as f64 * 1.3, (i % 11) as f64 * 0.9, i as f64 * 0.2))
+            .collect()
+    }
+
+    fn leaf_sizes(tree: &BvhTree) -> Vec<usize> {
+        tree.nodes
+            .iter()
+            .filter_map(|node| match node.kind {
+                LinearNodeKind::Leaf { count, .. } => Some(count as usize),
+                LinearNodeKind::Interior { .. } => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_hits_match_linear_search() {
+        let linear = spheres(grid());
+        for split_method in [SplitMethod::Sah, SplitMethod::Median] {
+            let tree = BvhTree::new_with_split_method(spheres(grid()), 0.0, 1.0, split_method);
+            for i in 0..100 {
+                // Rays from both sides, so that both child orders are traversed
+                let sign = if i % 2 == 0 { 1.0 } else { -1.0 };
+                let ray = Ray::new(
+                    Point3::new(4.0 - 9.0 * sign, 3.0, i as f64 * 0.4 - 1.0),
+                    Vec3::new(sign, (i % 5) as f64 * 0.1 - 0.2, 0.3 * sign),
+                    0.0,
+                );
+                let expected = linear.hit(&ray, 0.001, f64::INFINITY).map(|hit| hit.t);
+                let actual = tree.hit(&ray, 0.001, f64::INFINITY).map(|hit| hit.t);
+                assert_eq!(expected, actual, "{split_method:?}, ray {i}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_parallel_build_matches_serial() {
+        let centers = (0..2000)
+            .map(|i| {
+                Point3::new(
+                    (i * 7 % 13) as f64,
+                    (i * 5 % 17) as f64,
+                    (i % 19) as f64 * 0.5,
+                )
+            })
+            .collect::<Vec<_>>();
+        for split_method in [SplitMethod::Sah, SplitMethod::Median] {
+            let build = |parallel_threshold| {
+                random::seed(1);
+                Builder { parallel_threshold }.build(
+                    spheres(centers.clone()),
+                    0.0,
+                    1.0,
+                    split_method,
+                )
+            };
+            let serial = build(usize::MAX);
+            let parallel = build(2);
+
+            assert_eq!(serial.nodes, parallel.nodes, "{split_method:?}");
+            let boxes = |tree: &BvhTree| {
+                tree.hittables
+                    .iter()
+                    .map(|h| h.bounding_box(0.0, 1.0))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(boxes(&serial), boxes(&parallel), "{split_method:?}");
+        }
+    }
+
+    #[test]
+    fn test_sah_separates_clusters() {
+        let centers = (0..4)
+            .map(|i| Point3::new(i as f64, 0.0, 0.0))
+            .chain((0..4).map(|i| Point3::new(i as f64, 0.0, 100.0)));
+        let tree = BvhTree::new(spheres(centers), 0.0, 1.0);
+
+        let LinearNodeKind::Interior { second_child, axis } = tree.nodes[0].kind else {
+            panic!("the root should be split");
+        };
+        assert_eq!(2, axis);
+        assert_eq!(0.5, tree.nodes[1].bounding_box.max.z());
+        assert_eq!(99.5, tree.nodes[second_child as usize].bounding_box.min.z());
+    }
+
+    #[test]
+    fn test_leaf_sizes() {
+        let sizes = leaf_sizes(&BvhTree::new(spheres(grid()), 0.0, 1.0));
+        assert_eq!(200, sizes.iter().sum::<usize>());
+        assert!(sizes.iter().all(|&size| size <= MAX_LEAF_SIZE));
+
+        // Primitives at the same position cannot be separated, but are still split by count
+        let sizes = leaf_sizes(&BvhTree::new(spheres([Point3::default(); 10]), 0.0, 1.0));
+        assert_eq!(10, sizes.iter().sum::<usize>());
+        assert!(sizes.iter().all(|&size| size <= MAX_LEAF_SIZE));
+
+        let sizes = leaf_sizes(&BvhTree::new_with_split_method(
+            spheres(grid()),
+            0.0,
+            1.0,
+            SplitMethod::Median,
+        ));
+        assert!(sizes.iter().all(|&size| size == 1));
     }
 }
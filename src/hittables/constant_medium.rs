@@ -1,9 +1,10 @@
-use std::f64;
+use std::f64::{self, consts::PI};
 
 use rand::Rng;
 
 use crate::{
     materials::{Material, Scatter},
+    random,
     ray::Ray,
     textures::{SolidColor, Texture},
     vec3::{Color, Vec3},
@@ -43,15 +44,11 @@ impl<H: Hittable, T: Texture> ConstantMedium<H, T> {
 }
 
 impl<H: Hittable, T: Texture> Hittable for ConstantMedium<H, T> {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
         // 入射
-        let Some(mut hit_in) = self.boundary.hit(ray, -f64::INFINITY, f64::INFINITY) else {
-            return None;
-        };
+        let mut hit_in = self.boundary.hit(ray, -f64::INFINITY, f64::INFINITY)?;
         // 射出
-        let Some(mut hit_out) = self.boundary.hit(ray, hit_in.t + 0.0001, f64::INFINITY) else {
-            return None;
-        };
+        let mut hit_out = self.boundary.hit(ray, hit_in.t + 0.0001, f64::INFINITY)?;
 
         if hit_in.t < t_min {
             hit_in.t = t_min;
@@ -72,7 +69,7 @@ impl<H: Hittable, T: Texture> Hittable for ConstantMedium<H, T> {
 
         let ray_length = ray.direction.length();
         let distance_inside_boundary = (hit_out.t - hit_in.t) * ray_length;
-        let mut rng = rand::thread_rng();
+        let mut rng = random::rng();
         let hit_distance = self.neg_inv_density * rng.gen_range::<f64, _>(0.0..1.0).ln();
 
         if hit_distance > distance_inside_boundary {
@@ -120,7 +117,12 @@ impl<T: Texture> Isotropic<T> {
 impl<T: Texture> Material for Isotropic<T> {
     fn scatter(&self, ray: &Ray, hit: &Hit) -> Option<Scatter> {
         let ray = Ray::new(hit.p, Vec3::random_in_unit_sphere(), ray.time);
-        let attenuation = self.albedo.value(hit.u, hit.v, &hit.p);
-        Some(Scatter::new(attenuation, ray))
+        let attenuation = self.albedo.value_at(hit);
+        Some(Scatter::new_with_pdf(attenuation, ray, 1.0 / (4.0 * PI)))
+    }
+
+    /// Uniform over all directions
+    fn scattering_pdf(&self, _ray: &Ray, _hit: &Hit, _scattered: &Ray) -> f64 {
+        1.0 / (4.0 * PI)
     }
 }
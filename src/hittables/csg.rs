@@ -0,0 +1,215 @@
+use crate::ray::Ray;
+
+use super::{Aabb, Hit, Hittable};
+
+/// Boolean operations of constructive solid geometry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsgOperation {
+    /// Inside either object
+    Union,
+    /// Inside both objects
+    Intersection,
+    /// Inside the first object but not the second one
+    Difference,
+}
+
+impl CsgOperation {
+    fn contains(self, inside_a: bool, inside_b: bool) -> bool {
+        match self {
+            CsgOperation::Union => inside_a || inside_b,
+            CsgOperation::Intersection => inside_a && inside_b,
+            CsgOperation::Difference => inside_a && !inside_b,
+        }
+    }
+}
+
+/// Combination of two closed objects by a boolean operation.
+///
+/// The surfaces of the objects are walked along the ray, tracking whether the ray is inside
+/// each of them, and the first hit where the ray enters or leaves the combination is returned.
+#[derive(Debug, Clone)]
+pub struct Csg<A: Hittable, B: Hittable> {
+    a: A,
+    b: B,
+    operation: CsgOperation,
+}
+
+/// Distance past a hit where the next one is searched, as in `ConstantMedium`
+const STEP: f64 = 0.0001;
+
+impl<A: Hittable, B: Hittable> Csg<A, B> {
+    pub fn new(a: A, b: B, operation: CsgOperation) -> Csg<A, B> {
+        Csg { a, b, operation }
+    }
+
+    pub fn union(a: A, b: B) -> Csg<A, B> {
+        Csg::new(a, b, CsgOperation::Union)
+    }
+
+    pub fn intersection(a: A, b: B) -> Csg<A, B> {
+        Csg::new(a, b, CsgOperation::Intersection)
+    }
+
+    /// `a` with `b` carved out of it.
+    pub fn difference(a: A, b: B) -> Csg<A, B> {
+        Csg::new(a, b, CsgOperation::Difference)
+    }
+}
+
+impl<A: Hittable, B: Hittable> Hittable for Csg<A, B> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
+        let mut hit_a = self.a.hit(ray, t_min, f64::INFINITY);
+        let mut hit_b = self.b.hit(ray, t_min, f64::INFINITY);
+        // The ray starts inside a closed object if it leaves the object first
+        let mut inside_a = hit_a.as_ref().is_some_and(|hit| !hit.front_face);
+        let mut inside_b = hit_b.as_ref().is_some_and(|hit| !hit.front_face);
+
+        loop {
+            let is_a = match (&hit_a, &hit_b) {
+                (None, None) => return None,
+                (Some(a), Some(b)) => a.t <= b.t,
+                (a, _) => a.is_some(),
+            };
+            let hit = if is_a { &hit_a } else { &hit_b }.as_ref().unwrap();
+            if hit.t > t_max {
+                return None;
+            }
+
+            let was_inside = self.operation.contains(inside_a, inside_b);
+            if is_a {
+                inside_a = hit.front_face;
+            } else {
+                inside_b = hit.front_face;
+            }
+            let is_inside = self.operation.contains(inside_a, inside_b);
+
+            if was_inside != is_inside {
+                let mut hit = if is_a { hit_a } else { hit_b }.unwrap();
+                let mut outward_normal = if hit.front_face {
+                    hit.normal
+                } else {
+                    -hit.normal
+                };
+                if self.operation == CsgOperation::Difference && !is_a {
+                    // The inside of `b` is the outside of the carved surface
+                    outward_normal = -outward_normal;
+                }
+                hit.set_face_normal(ray, outward_normal);
+                return Some(hit);
+            }
+
+            let t = hit.t + STEP;
+            if is_a {
+                hit_a = self.a.hit(ray, t, f64::INFINITY);
+            } else {
+                hit_b = self.b.hit(ray, t, f64::INFINITY);
+            }
+        }
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        let aabb_a = self.a.bounding_box(time0, time1);
+        let aabb_b = self.b.bounding_box(time0, time1);
+        match self.operation {
+            CsgOperation::Union => Some(aabb_a?.union(&aabb_b?)),
+            CsgOperation::Intersection => match (aabb_a, aabb_b) {
+                (Some(a), Some(b)) => Some(a.intersection(&b)),
+                (a, b) => a.or(b),
+            },
+            CsgOperation::Difference => aabb_a,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hittables::{Box as HittableBox, Sphere},
+        materials::{Lambertian, Metal},
+        vec3::{Color, Point3, Vec3},
+    };
+
+    use super::*;
+
+    fn sphere() -> Sphere<Lambertian<crate::textures::SolidColor>> {
+        Sphere::new(
+            Point3::default(),
+            1.0,
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        )
+    }
+
+    /// A box with a square hole along z, through the sphere
+    fn hole() -> HittableBox {
+        HittableBox::new(
+            Point3::new(-0.5, -0.5, -2.0),
+            Point3::new(0.5, 0.5, 2.0),
+            Metal::new(Color::new(0.8, 0.8, 0.8), 0.0),
+        )
+    }
+
+    fn ray(origin: Point3, direction: Vec3) -> Ray {
+        Ray::new(origin, direction, 0.0)
+    }
+
+    #[test]
+    fn test_difference() {
+        let csg = Csg::difference(sphere(), hole());
+
+        // Through the hole
+        let through = ray(Point3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(csg.hit(&through, 0.001, f64::INFINITY).is_none());
+
+        // Into the wall of the hole, whose normal faces into the hole
+        let sideways = ray(Point3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let hit = csg.hit(&sideways, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 0.5).abs() < 1e-10);
+        assert!(hit.front_face);
+        assert!((hit.normal - Vec3::new(-1.0, 0.0, 0.0)).length() < 1e-10);
+
+        // Beside the hole, the sphere itself is hit
+        let beside = ray(Point3::new(0.75, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        let hit = csg.hit(&beside, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.p.z() + (1.0 - 0.75f64 * 0.75).sqrt()).abs() < 1e-10);
+        assert!(hit.front_face);
+
+        assert!(csg.hit(&sideways, 0.001, 0.4).is_none());
+
+        // From inside the wall, the ray leaves through the hole
+        let outward = ray(Point3::new(0.75, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        let hit = csg.hit(&outward, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 0.25).abs() < 1e-10);
+        assert!(!hit.front_face);
+        assert!((hit.normal - Vec3::new(1.0, 0.0, 0.0)).length() < 1e-10);
+
+        let aabb = csg.bounding_box(0.0, 1.0).unwrap();
+        assert_eq!(sphere().bounding_box(0.0, 1.0).unwrap(), aabb);
+    }
+
+    #[test]
+    fn test_union_and_intersection() {
+        let ray = ray(Point3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+
+        let union = Csg::union(sphere(), hole());
+        let hit = union.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 3.0).abs() < 1e-10);
+        // The surface of the sphere inside the box is skipped
+        let hit = union.hit(&ray, hit.t + 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 7.0).abs() < 1e-10);
+        assert!(!hit.front_face);
+
+        let intersection = Csg::intersection(sphere(), hole());
+        let hit = intersection.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 4.0).abs() < 1e-10);
+        let hit = intersection
+            .hit(&ray, hit.t + 0.001, f64::INFINITY)
+            .unwrap();
+        assert!((hit.t - 6.0).abs() < 1e-10);
+
+        let aabb = intersection.bounding_box(0.0, 1.0).unwrap();
+        assert_eq!(
+            Aabb::new(Point3::new(-0.5, -0.5, -1.0), Point3::new(0.5, 0.5, 1.0)),
+            aabb
+        );
+    }
+}
@@ -0,0 +1,513 @@
+use std::f64::consts::SQRT_2;
+
+use crate::{
+    materials::Material,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{Aabb, Hit, Hittable};
+
+/// Cross section of a `Curve`.
+#[derive(Debug, Clone, Copy)]
+pub enum CurveShape {
+    /// Tube, such as a hair
+    Round,
+    /// Flat strip facing a normal interpolated from the start to the end, such as a blade of
+    /// grass; it gets thinner as it turns edge-on to the ray
+    Ribbon { normal0: Vec3, normal1: Vec3 },
+}
+
+/// Thin cubic curve whose width varies linearly from one end to the other.
+///
+/// Rays are intersected by subdividing the curve until its pieces are nearly straight, in a frame
+/// where the ray runs along the z axis (Nakamaru and Ohno). The texture coordinate `u` runs along
+/// the curve and `v` across it.
+#[derive(Debug, Clone)]
+pub struct Curve<M: Material> {
+    /// Bézier control points of the part of the curve from `u_range.0` to `u_range.1`
+    points: [Point3; 4],
+    widths: [f64; 2],
+    /// With the normals of a ribbon at both ends of the part
+    shape: CurveShape,
+    u_range: (f64, f64),
+    material: M,
+}
+
+impl<M: Material> Curve<M> {
+    /// Bézier curve through `points[0]` and `points[3]`.
+    pub fn new(
+        points: [Point3; 4],
+        width0: f64,
+        width1: f64,
+        shape: CurveShape,
+        material: M,
+    ) -> Curve<M> {
+        assert!(
+            width0 >= 0.0 && width1 >= 0.0,
+            "the widths must not be negative"
+        );
+        let shape = match shape {
+            CurveShape::Round => CurveShape::Round,
+            CurveShape::Ribbon { normal0, normal1 } => {
+                assert!(
+                    !normal0.is_near_zero() && !normal1.is_near_zero(),
+                    "the normals must not be zero"
+                );
+                CurveShape::Ribbon {
+                    normal0: normal0.unit(),
+                    normal1: normal1.unit(),
+                }
+            }
+        };
+        Curve {
+            points,
+            widths: [width0, width1],
+            shape,
+            u_range: (0.0, 1.0),
+            material,
+        }
+    }
+
+    /// Uniform cubic B-spline segment, which joins smoothly with the segments of the neighboring
+    /// windows of control points.
+    pub fn new_b_spline(
+        points: [Point3; 4],
+        width0: f64,
+        width1: f64,
+        shape: CurveShape,
+        material: M,
+    ) -> Curve<M> {
+        let [p0, p1, p2, p3] = points;
+        let bezier = [
+            (p0 + 4.0 * p1 + p2) / 6.0,
+            (2.0 * p1 + p2) / 3.0,
+            (p1 + 2.0 * p2) / 3.0,
+            (p1 + 4.0 * p2 + p3) / 6.0,
+        ];
+        Curve::new(bezier, width0, width1, shape, material)
+    }
+
+    /// Splits the curve into `segments` parts of equal parameter range, whose bounding boxes
+    /// are tighter around a long curve.
+    pub fn split(&self, segments: usize) -> Vec<Curve<M>>
+    where
+        M: Clone,
+    {
+        (0..segments)
+            .map(|i| {
+                let a = i as f64 / segments as f64;
+                let b = (i + 1) as f64 / segments as f64;
+                let p = &self.points;
+                let shape = match self.shape {
+                    CurveShape::Round => CurveShape::Round,
+                    CurveShape::Ribbon { normal0, normal1 } => CurveShape::Ribbon {
+                        normal0: lerp(normal0, normal1, a),
+                        normal1: lerp(normal0, normal1, b),
+                    },
+                };
+                Curve {
+                    points: [
+                        blossom(p, a, a, a),
+                        blossom(p, a, a, b),
+                        blossom(p, a, b, b),
+                        blossom(p, b, b, b),
+                    ],
+                    widths: [self.width(a), self.width(b)],
+                    shape,
+                    u_range: (
+                        lerp(self.u_range.0, self.u_range.1, a),
+                        lerp(self.u_range.0, self.u_range.1, b),
+                    ),
+                    material: self.material.clone(),
+                }
+            })
+            .collect()
+    }
+
+    fn width(&self, s: f64) -> f64 {
+        lerp(self.widths[0], self.widths[1], s)
+    }
+
+    /// Subdivides the part `cp` from `s0` to `s1` of the curve in ray space, and returns the
+    /// `(z, s, v)` of the nearest hit.
+    #[allow(clippy::too_many_arguments)]
+    fn recurse(
+        &self,
+        cp: &[Vec3; 4],
+        s0: f64,
+        s1: f64,
+        depth: u32,
+        direction: Vec3,
+        z_min: f64,
+        z_max: f64,
+    ) -> Option<(f64, f64, f64)> {
+        let half = 0.5 * self.width(s0).max(self.width(s1));
+        let min = cp.iter().fold(
+            Vec3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            |a, p| Vec3::new(a.x().min(p.x()), a.y().min(p.y()), a.z().min(p.z())),
+        );
+        let max = cp.iter().fold(
+            Vec3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+            |a, p| Vec3::new(a.x().max(p.x()), a.y().max(p.y()), a.z().max(p.z())),
+        );
+        if min.x() - half > 0.0
+            || max.x() + half < 0.0
+            || min.y() - half > 0.0
+            || max.y() + half < 0.0
+            || min.z() - half > z_max
+            || max.z() + half < z_min
+        {
+            return None;
+        }
+
+        if depth == 0 {
+            return self.hit_segment(cp, s0, s1, direction, z_min, z_max);
+        }
+        let (a, b) = subdivide(cp);
+        let middle = 0.5 * (s0 + s1);
+        let first = self.recurse(&a, s0, middle, depth - 1, direction, z_min, z_max);
+        let second = self.recurse(&b, middle, s1, depth - 1, direction, z_min, z_max);
+        match (first, second) {
+            (Some(first), Some(second)) if second.0 < first.0 => Some(second),
+            (Some(first), _) => Some(first),
+            (None, second) => second,
+        }
+    }
+
+    /// Treats the part as the straight segment between its ends.
+    fn hit_segment(
+        &self,
+        cp: &[Vec3; 4],
+        s0: f64,
+        s1: f64,
+        direction: Vec3,
+        z_min: f64,
+        z_max: f64,
+    ) -> Option<(f64, f64, f64)> {
+        // The point closest to the ray must lie between the planes through the ends,
+        // perpendicular to the curve there
+        let edge = (cp[1].y() - cp[0].y()) * -cp[0].y() + cp[0].x() * (cp[0].x() - cp[1].x());
+        if edge < 0.0 {
+            return None;
+        }
+        let edge = (cp[2].y() - cp[3].y()) * -cp[3].y() + cp[3].x() * (cp[3].x() - cp[2].x());
+        if edge < 0.0 {
+            return None;
+        }
+
+        let segment = Vec3::new(cp[3].x() - cp[0].x(), cp[3].y() - cp[0].y(), 0.0);
+        let denominator = segment.length_squared();
+        let w = if denominator == 0.0 {
+            0.0
+        } else {
+            (-(cp[0].x() * segment.x() + cp[0].y() * segment.y()) / denominator).clamp(0.0, 1.0)
+        };
+        let s = lerp(s0, s1, w);
+        let mut width = self.width(s);
+        if let CurveShape::Ribbon { normal0, normal1 } = self.shape {
+            width *= lerp(normal0, normal1, s).unit().dot(direction).abs();
+        }
+
+        let c = bezier(cp, w);
+        let distance_squared = c.x() * c.x() + c.y() * c.y();
+        let radius = 0.5 * width;
+        if distance_squared > radius * radius {
+            return None;
+        }
+        let z = match self.shape {
+            CurveShape::Round => c.z() - (radius * radius - distance_squared).sqrt(),
+            CurveShape::Ribbon { .. } => c.z(),
+        };
+        if z < z_min || z > z_max {
+            return None;
+        }
+
+        // Which side of the curve the ray passes
+        let tangent = bezier_derivative(cp, w);
+        let side = tangent.y() * c.x() - tangent.x() * c.y();
+        let offset = if width > 0.0 {
+            distance_squared.sqrt() / width
+        } else {
+            0.0
+        };
+        let v = if side > 0.0 {
+            0.5 + offset
+        } else {
+            0.5 - offset
+        };
+        Some((z, s, v))
+    }
+}
+
+impl<M: Material> Hittable for Curve<M> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
+        // Frame where the ray starts at the origin and runs along z
+        let length = ray.direction.length();
+        let direction = ray.direction / length;
+        let axis = if direction.x().abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let x = direction.cross(axis).unit();
+        let y = direction.cross(x);
+        let cp = self.points.map(|p| {
+            let p = p - ray.origin;
+            Vec3::new(p.dot(x), p.dot(y), p.dot(direction))
+        });
+
+        // Subdivides until the pieces deviate from straight by a fraction of the width
+        let curvature = (0..2)
+            .map(|i| (cp[i] - 2.0 * cp[i + 1] + cp[i + 2]).length())
+            .fold(0.0, f64::max);
+        let epsilon = 0.05 * self.widths[0].max(self.widths[1]);
+        let depth = ((SQRT_2 * 6.0 * curvature / (8.0 * epsilon)).log2() / 2.0)
+            .floor()
+            .clamp(0.0, 10.0) as u32;
+
+        let (z, s, v) = self.recurse(
+            &cp,
+            0.0,
+            1.0,
+            depth,
+            direction,
+            t_min * length,
+            t_max * length,
+        )?;
+        let t = z / length;
+
+        let outward_normal = match self.shape {
+            CurveShape::Round => {
+                // Away from the axis of the tube
+                let tangent = bezier_derivative(&self.points, s).unit();
+                let offset = ray.at(t) - bezier(&self.points, s);
+                let normal = offset - tangent.dot(offset) * tangent;
+                if normal.is_near_zero() {
+                    -direction
+                } else {
+                    normal.unit()
+                }
+            }
+            CurveShape::Ribbon { normal0, normal1 } => lerp(normal0, normal1, s).unit(),
+        };
+        let u = lerp(self.u_range.0, self.u_range.1, s);
+        Some(Hit::new_with_outward_normal(
+            ray,
+            t,
+            u,
+            v,
+            outward_normal,
+            &self.material,
+        ))
+    }
+
+    /// The curve lies within the convex hull of its control points.
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
+        let half = 0.5 * self.widths[0].max(self.widths[1]);
+        let padding = Vec3::new(half, half, half);
+        let aabb = self.points[1..]
+            .iter()
+            .fold(Aabb::new(self.points[0], self.points[0]), |aabb, &p| {
+                aabb.union(&Aabb::new(p, p))
+            });
+        Some(Aabb::new(aabb.min - padding, aabb.max + padding))
+    }
+}
+
+fn lerp<T>(a: T, b: T, s: f64) -> T
+where
+    T: std::ops::Mul<f64, Output = T> + std::ops::Add<Output = T>,
+{
+    a * (1.0 - s) + b * s
+}
+
+fn bezier(cp: &[Vec3; 4], s: f64) -> Vec3 {
+    blossom(cp, s, s, s)
+}
+
+fn bezier_derivative(cp: &[Vec3; 4], s: f64) -> Vec3 {
+    let r = 1.0 - s;
+    3.0 * (r * r * (cp[1] - cp[0]) + 2.0 * r * s * (cp[2] - cp[1]) + s * s * (cp[3] - cp[2]))
+}
+
+/// Blossom of the cubic Bézier curve; with `(a, a, a)`, `(a, a, b)`, `(a, b, b)` and `(b, b, b)`
+/// it gives the control points of the part from `a` to `b`.
+fn blossom(cp: &[Vec3; 4], s0: f64, s1: f64, s2: f64) -> Vec3 {
+    let a = [
+        lerp(cp[0], cp[1], s0),
+        lerp(cp[1], cp[2], s0),
+        lerp(cp[2], cp[3], s0),
+    ];
+    let b = [lerp(a[0], a[1], s1), lerp(a[1], a[2], s1)];
+    lerp(b[0], b[1], s2)
+}
+
+/// Halves of the curve by de Casteljau's algorithm.
+fn subdivide(cp: &[Vec3; 4]) -> ([Vec3; 4], [Vec3; 4]) {
+    let a = [
+        0.5 * (cp[0] + cp[1]),
+        0.5 * (cp[1] + cp[2]),
+        0.5 * (cp[2] + cp[3]),
+    ];
+    let b = [0.5 * (a[0] + a[1]), 0.5 * (a[1] + a[2])];
+    let middle = 0.5 * (b[0] + b[1]);
+    ([cp[0], a[0], b[0], middle], [middle, b[1], a[2], cp[3]])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{materials::Lambertian, textures::SolidColor, vec3::Color};
+
+    use super::*;
+
+    /// Arch from (-1, 0, 0) to (1, 0, 0) rising to 0.75 in the middle
+    fn arch(shape: CurveShape) -> Curve<Lambertian<SolidColor>> {
+        Curve::new(
+            [
+                Point3::new(-1.0, 0.0, 0.0),
+                Point3::new(-1.0, 1.0, 0.0),
+                Point3::new(1.0, 1.0, 0.0),
+                Point3::new(1.0, 0.0, 0.0),
+            ],
+            0.1,
+            0.1,
+            shape,
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        )
+    }
+
+    #[test]
+    fn test_round_matches_cylinder() {
+        let curve = Curve::new(
+            [
+                Point3::new(-1.0, 0.0, 0.0),
+                Point3::new(-1.0 / 3.0, 0.0, 0.0),
+                Point3::new(1.0 / 3.0, 0.0, 0.0),
+                Point3::new(1.0, 0.0, 0.0),
+            ],
+            0.2,
+            0.2,
+            CurveShape::Round,
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        );
+
+        for y in [0.0, 0.05, -0.09] {
+            let ray = Ray::new(Point3::new(0.3, y, -5.0), Vec3::new(0.0, 0.0, 2.0), 0.0);
+            let hit = curve.hit(&ray, 0.001, f64::INFINITY).unwrap();
+            let z = -(0.01 - y * y).sqrt();
+            assert!((hit.t - (5.0 + z) / 2.0).abs() < 1e-10);
+            assert!((hit.normal - Vec3::new(0.0, y, z) / 0.1).length() < 1e-10);
+            assert!((hit.u - 0.65).abs() < 1e-10);
+            assert!(hit.front_face);
+        }
+
+        let ray = Ray::new(Point3::new(0.3, 0.11, -5.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+        assert!(curve.hit(&ray, 0.001, f64::INFINITY).is_none());
+        let ray = Ray::new(Point3::new(1.2, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+        assert!(curve.hit(&ray, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn test_curved() {
+        let curve = arch(CurveShape::Round);
+        let top = bezier(&curve.points, 0.5);
+        assert!((top - Point3::new(0.0, 0.75, 0.0)).length() < 1e-10);
+
+        // Down onto the top of the tube
+        let ray = Ray::new(Point3::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        let hit = curve.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 4.2).abs() < 1e-3);
+        assert!((hit.u - 0.5).abs() < 1e-3);
+        assert!((hit.normal - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-2);
+
+        // Sideways through both legs of the arch, the nearer one first
+        let ray = Ray::new(Point3::new(-5.0, 0.1, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        let hit = curve.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!(hit.p.x() < -0.9);
+        assert!(hit.u < 0.5);
+
+        // Under the arch
+        let ray = Ray::new(Point3::new(0.0, 0.3, -5.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+        assert!(curve.hit(&ray, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn test_ribbon() {
+        let facing = |normal: Vec3| {
+            arch(CurveShape::Ribbon {
+                normal0: normal,
+                normal1: normal,
+            })
+        };
+        let ray = Ray::new(Point3::new(0.0, 0.78, -5.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+
+        let ribbon = facing(Vec3::new(0.0, 0.0, 1.0));
+        let hit = ribbon.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 5.0).abs() < 1e-10);
+        assert!((hit.normal - Vec3::new(0.0, 0.0, -1.0)).length() < 1e-10);
+        assert!(!hit.front_face);
+        assert!(hit.v > 0.5);
+
+        // Edge-on to the ray, the ribbon vanishes
+        assert!(facing(Vec3::new(0.0, 1.0, 0.0))
+            .hit(&ray, 0.001, f64::INFINITY)
+            .is_none());
+        // Turned by 60 degrees, it is half as wide
+        let turned = facing(Vec3::new(3.0f64.sqrt(), 0.0, 1.0));
+        assert!(turned.hit(&ray, 0.001, f64::INFINITY).is_none());
+        let ray = Ray::new(Point3::new(0.0, 0.77, -5.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+        assert!(turned.hit(&ray, 0.001, f64::INFINITY).is_some());
+    }
+
+    #[test]
+    fn test_b_spline() {
+        // Evenly spaced control points on a line give the segment between the middle two
+        let curve = Curve::new_b_spline(
+            [
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(1.0, 0.0, 0.0),
+                Point3::new(2.0, 0.0, 0.0),
+                Point3::new(3.0, 0.0, 0.0),
+            ],
+            0.2,
+            0.0,
+            CurveShape::Round,
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        );
+        let aabb = curve.bounding_box(0.0, 1.0).unwrap();
+        assert!((aabb.min - Point3::new(0.9, -0.1, -0.1)).length() < 1e-10);
+        assert!((aabb.max - Point3::new(2.1, 0.1, 0.1)).length() < 1e-10);
+    }
+
+    #[test]
+    fn test_split() {
+        let curve = arch(CurveShape::Round);
+        let parts = curve.split(4);
+        let whole = curve.bounding_box(0.0, 1.0).unwrap();
+
+        for part in &parts {
+            let aabb = part.bounding_box(0.0, 1.0).unwrap();
+            assert!((0..3).all(|i| whole.min[i] <= aabb.min[i] && aabb.max[i] <= whole.max[i]));
+        }
+        // The parts join up into the same curve
+        for s in [0.3, 0.5, 0.85] {
+            let p = bezier(&curve.points, s);
+            let ray = Ray::new(
+                Point3::new(p.x(), p.y(), -5.0),
+                Vec3::new(0.0, 0.0, 1.0),
+                0.0,
+            );
+            let expected = curve.hit(&ray, 0.001, f64::INFINITY).unwrap();
+            let actual = parts
+                .iter()
+                .filter_map(|part| part.hit(&ray, 0.001, f64::INFINITY))
+                .min_by(|a, b| a.t.total_cmp(&b.t))
+                .unwrap();
+            assert!((expected.t - 4.95).abs() < 1e-3);
+            assert!((expected.t - actual.t).abs() < 1e-3);
+            assert!((expected.u - s).abs() < 1e-2);
+            assert!((expected.u - actual.u).abs() < 1e-2);
+        }
+    }
+}
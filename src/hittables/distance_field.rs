@@ -0,0 +1,175 @@
+use std::f64::consts::PI;
+
+use crate::{
+    materials::Material,
+    ray::Ray,
+    sdf::Sdf,
+    vec3::{Point3, Vec3},
+};
+
+use super::{quadric::azimuth, Aabb, Hit, Hittable};
+
+/// Surface of a signed distance function, found by sphere tracing within `bounding_box`.
+///
+/// The texture coordinates are those of a sphere at the direction of the normal.
+#[derive(Debug, Clone)]
+pub struct DistanceField<S: Sdf, M: Material> {
+    sdf: S,
+    bounding_box: Aabb,
+    material: M,
+}
+
+/// Distance to the surface where the ray is considered to hit it
+const HIT_DISTANCE: f64 = 1e-5;
+/// Offset of the samples of the distance whose differences give the normal
+const NORMAL_OFFSET: f64 = 1e-5;
+const MAX_STEPS: usize = 512;
+
+impl<S: Sdf, M: Material> DistanceField<S, M> {
+    /// `bounding_box` must enclose the whole surface, which is cut off outside of it.
+    pub fn new(sdf: S, bounding_box: Aabb, material: M) -> DistanceField<S, M> {
+        DistanceField {
+            sdf,
+            bounding_box,
+            material,
+        }
+    }
+
+    /// The gradient of the distance by the tetrahedron technique, with four samples.
+    fn normal(&self, p: Point3) -> Vec3 {
+        [
+            Vec3::new(1.0, -1.0, -1.0),
+            Vec3::new(-1.0, -1.0, 1.0),
+            Vec3::new(-1.0, 1.0, -1.0),
+            Vec3::new(1.0, 1.0, 1.0),
+        ]
+        .into_iter()
+        .map(|k| k * self.sdf.distance(p + k * NORMAL_OFFSET))
+        .sum::<Vec3>()
+        .unit()
+    }
+}
+
+impl<S: Sdf, M: Material> Hittable for DistanceField<S, M> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
+        let (mut t, t_end) = self.bounding_box.hit_interval(ray, t_min, t_max)?;
+        let length = ray.direction.length();
+        for _ in 0..MAX_STEPS {
+            // No part of the surface is nearer than the distance, so the ray can safely advance
+            // that far; from inside, the absolute value finds the way out
+            let distance = self.sdf.distance(ray.at(t)).abs();
+            if distance < HIT_DISTANCE {
+                let p = ray.at(t);
+                let outward_normal = self.normal(p);
+                let u = azimuth(outward_normal);
+                let v = (-outward_normal.y()).clamp(-1.0, 1.0).acos() / PI;
+                return Some(Hit::new_with_outward_normal(
+                    ray,
+                    t,
+                    u,
+                    v,
+                    outward_normal,
+                    &self.material,
+                ));
+            }
+            t += distance / length;
+            if t > t_end {
+                return None;
+            }
+        }
+        None
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
+        Some(self.bounding_box)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hittables::Sphere,
+        materials::Lambertian,
+        sdf::{self, Mandelbulb, RoundBox},
+        vec3::Color,
+    };
+
+    use super::*;
+
+    fn cube(half: f64) -> Aabb {
+        Aabb::new(
+            Point3::new(-half, -half, -half),
+            Point3::new(half, half, half),
+        )
+    }
+
+    #[test]
+    fn test_matches_sphere() {
+        let field = DistanceField::new(
+            sdf::Sphere::new(1.0),
+            cube(1.0),
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        );
+        let sphere = Sphere::new(
+            Point3::default(),
+            1.0,
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        );
+
+        for (x, y) in [(0.0, 0.0), (0.5, -0.3), (0.9, 0.4), (1.1, 0.0)] {
+            let ray = Ray::new(Point3::new(x, y, -5.0), Vec3::new(0.1, 0.0, 2.0), 0.0);
+            let expected = sphere.hit(&ray, 0.001, f64::INFINITY);
+            let actual = field.hit(&ray, 0.001, f64::INFINITY);
+            assert_eq!(expected.is_some(), actual.is_some());
+            if let (Some(expected), Some(actual)) = (expected, actual) {
+                assert!((expected.t - actual.t).abs() < 1e-4);
+                assert!((expected.normal - actual.normal).length() < 1e-3);
+                assert!(actual.front_face);
+            }
+        }
+
+        // From inside, the ray finds its way out
+        let ray = Ray::new(Point3::default(), Vec3::new(0.0, 1.0, 0.0), 0.0);
+        let hit = field.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 1.0).abs() < 1e-4);
+        assert!(!hit.front_face);
+    }
+
+    #[test]
+    fn test_smooth_union_of_round_boxes() {
+        let round_box = RoundBox::new(Vec3::new(1.0, 1.0, 1.0), 0.2);
+        let sdf = round_box
+            .clone()
+            .smooth_union(round_box.translate(Vec3::new(0.0, 2.2, 0.0)), 0.5);
+        // The blend bulges out a little beyond the boxes
+        let bounding_box = Aabb::new(Point3::new(-1.5, -1.5, -1.5), Point3::new(1.5, 3.7, 1.5));
+        let field = DistanceField::new(
+            sdf,
+            bounding_box,
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        );
+
+        // The gap between the boxes, halfway up it, is filled in at the points
+        let ray = Ray::new(Point3::new(0.0, 1.1, -5.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+        let hit = field.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        // where each box is 0.125 away, half of the smoothing
+        assert!((hit.t - 4.075).abs() < 1e-4);
+        assert!((hit.normal - Vec3::new(0.0, 0.0, -1.0)).length() < 1e-3);
+    }
+
+    #[test]
+    fn test_mandelbulb() {
+        let field = DistanceField::new(
+            Mandelbulb::default(),
+            cube(1.5),
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        );
+
+        let ray = Ray::new(Point3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+        let hit = field.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!(3.5 < hit.t && hit.t < 5.0);
+
+        let ray = Ray::new(Point3::new(1.4, 1.4, -5.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+        assert!(field.hit(&ray, 0.001, f64::INFINITY).is_none());
+    }
+}
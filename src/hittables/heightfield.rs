@@ -0,0 +1,317 @@
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use image::{io::Reader, DynamicImage};
+
+use crate::{
+    materials::Material,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{triangle::intersect, Aabb, Hit, Hittable};
+
+/// Terrain whose height at each pixel of a grayscale image, from black to white, spans `size.y`
+/// above `min`.
+///
+/// The image covers `size.x` along x and `size.z` along z, with its top row at `min.z`. Each cell
+/// between four pixels is made of two triangles, which a ray visits in order by walking the grid.
+/// The texture coordinates match those of an `ImageTexture` of the same image.
+#[derive(Debug, Clone)]
+pub struct Heightfield<M: Material> {
+    /// Heights above `min.y`, row by row
+    heights: Vec<f64>,
+    columns: usize,
+    rows: usize,
+    min: Point3,
+    size: Vec3,
+    /// Size of a cell along x and z
+    cell: (f64, f64),
+    bounding_box: Aabb,
+    material: M,
+}
+
+impl<M: Material> Heightfield<M> {
+    /// Panics if the image is smaller than 2×2 pixels.
+    pub fn new(image: &DynamicImage, min: Point3, size: Vec3, material: M) -> Heightfield<M> {
+        let image = image.to_luma16();
+        let (columns, rows) = (image.width() as usize, image.height() as usize);
+        assert!(
+            columns >= 2 && rows >= 2,
+            "the image must be at least 2×2 pixels"
+        );
+        let heights = image
+            .pixels()
+            .map(|pixel| pixel[0] as f64 / u16::MAX as f64 * size.y())
+            .collect::<Vec<_>>();
+
+        let lowest = heights.iter().copied().fold(f64::INFINITY, f64::min);
+        let highest = heights.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        // Pads the box so that flat terrain still has some volume
+        let bounding_box = Aabb::new(
+            Point3::new(min.x(), min.y() + lowest - 0.0001, min.z()),
+            Point3::new(
+                min.x() + size.x(),
+                min.y() + highest + 0.0001,
+                min.z() + size.z(),
+            ),
+        );
+
+        Heightfield {
+            heights,
+            columns,
+            rows,
+            min,
+            size,
+            cell: (
+                size.x() / (columns - 1) as f64,
+                size.z() / (rows - 1) as f64,
+            ),
+            bounding_box,
+            material,
+        }
+    }
+
+    pub fn new_with_filename(
+        filename: impl AsRef<Path>,
+        min: Point3,
+        size: Vec3,
+        material: M,
+    ) -> Result<Heightfield<M>> {
+        let image = Reader::open(filename)?.decode()?;
+        if image.width() < 2 || image.height() < 2 {
+            bail!(
+                "the image must be at least 2×2 pixels, found {}×{}",
+                image.width(),
+                image.height()
+            );
+        }
+        Ok(Heightfield::new(&image, min, size, material))
+    }
+
+    fn vertex(&self, column: usize, row: usize) -> Point3 {
+        Point3::new(
+            self.min.x() + column as f64 * self.cell.0,
+            self.min.y() + self.heights[row * self.columns + column],
+            self.min.z() + row as f64 * self.cell.1,
+        )
+    }
+
+    /// The nearest hit on the two triangles of the cell, with the normal facing up.
+    fn hit_cell(
+        &self,
+        ray: &Ray,
+        column: usize,
+        row: usize,
+        t_min: f64,
+        t_max: f64,
+    ) -> Option<(f64, Vec3)> {
+        let p00 = self.vertex(column, row);
+        let p10 = self.vertex(column + 1, row);
+        let p01 = self.vertex(column, row + 1);
+        let p11 = self.vertex(column + 1, row + 1);
+        [(p00, p01, p10), (p11, p10, p01)]
+            .into_iter()
+            .filter_map(|(v0, v1, v2)| {
+                let (t, _, _) = intersect(ray, v0, v1, v2, t_min, t_max)?;
+                Some((t, (v1 - v0).cross(v2 - v0)))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+    }
+}
+
+/// Index of the cell containing `x` along an axis, and how the ray steps through the cells.
+///
+/// Returns `(index, step, t_next, t_delta)`, where `t_next` is where the ray crosses into the next
+/// cell and `t_delta` is the distance in `t` between crossings.
+fn walk(
+    origin: f64,
+    direction: f64,
+    x: f64,
+    min: f64,
+    cell: f64,
+    cells: usize,
+) -> (isize, isize, f64, f64) {
+    let index = (((x - min) / cell).floor() as isize).clamp(0, cells as isize - 1);
+    if direction > 0.0 {
+        let boundary = min + (index + 1) as f64 * cell;
+        (index, 1, (boundary - origin) / direction, cell / direction)
+    } else if direction < 0.0 {
+        let boundary = min + index as f64 * cell;
+        (
+            index,
+            -1,
+            (boundary - origin) / direction,
+            -cell / direction,
+        )
+    } else {
+        (index, 0, f64::INFINITY, f64::INFINITY)
+    }
+}
+
+impl<M: Material> Hittable for Heightfield<M> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
+        let (t_enter, t_exit) = self.bounding_box.hit_interval(ray, t_min, t_max)?;
+        let p = ray.at(t_enter);
+        let (columns, rows) = (self.columns - 1, self.rows - 1);
+        let (mut column, step_x, mut t_next_x, t_delta_x) = walk(
+            ray.origin.x(),
+            ray.direction.x(),
+            p.x(),
+            self.min.x(),
+            self.cell.0,
+            columns,
+        );
+        let (mut row, step_z, mut t_next_z, t_delta_z) = walk(
+            ray.origin.z(),
+            ray.direction.z(),
+            p.z(),
+            self.min.z(),
+            self.cell.1,
+            rows,
+        );
+
+        // Cells are visited in the order the ray crosses them, so the first hit is the nearest
+        let (t, normal) = loop {
+            if let Some(hit) = self.hit_cell(ray, column as usize, row as usize, t_min, t_max) {
+                break hit;
+            }
+            if t_next_x.min(t_next_z) > t_exit {
+                return None;
+            }
+            if t_next_x < t_next_z {
+                column += step_x;
+                t_next_x += t_delta_x;
+            } else {
+                row += step_z;
+                t_next_z += t_delta_z;
+            }
+            if !(0..columns as isize).contains(&column) || !(0..rows as isize).contains(&row) {
+                return None;
+            }
+        };
+
+        let p = ray.at(t);
+        let u = (p.x() - self.min.x()) / self.size.x();
+        let v = 1.0 - (p.z() - self.min.z()) / self.size.z();
+        Some(Hit::new_with_outward_normal(
+            ray,
+            t,
+            u.clamp(0.0, 1.0),
+            v.clamp(0.0, 1.0),
+            normal.unit(),
+            &self.material,
+        ))
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
+        Some(self.bounding_box)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::GrayImage;
+
+    use crate::{hittables::Triangle, materials::Lambertian, textures::SolidColor, vec3::Color};
+
+    use super::*;
+
+    /// A ridge along z in the middle column of a 3×3 image, on a 2×2 square
+    fn ridge() -> Heightfield<Lambertian<SolidColor>> {
+        let image = GrayImage::from_raw(3, 3, vec![0, 255, 0, 0, 255, 0, 0, 255, 0]).unwrap();
+        Heightfield::new(
+            &DynamicImage::ImageLuma8(image),
+            Point3::new(-1.0, 0.0, -1.0),
+            Vec3::new(2.0, 1.0, 2.0),
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        )
+    }
+
+    #[test]
+    fn test_hit_from_above() {
+        let heightfield = ridge();
+        let down = Vec3::new(0.0, -1.0, 0.0);
+
+        let hit = heightfield
+            .hit(
+                &Ray::new(Point3::new(0.0, 5.0, 0.3), down, 0.0),
+                0.001,
+                f64::INFINITY,
+            )
+            .unwrap();
+        assert!((hit.t - 4.0).abs() < 1e-10);
+        assert!(hit.front_face);
+
+        // Halfway down the slope facing -x
+        let hit = heightfield
+            .hit(
+                &Ray::new(Point3::new(-0.5, 5.0, 0.7), down, 0.0),
+                0.001,
+                f64::INFINITY,
+            )
+            .unwrap();
+        assert!((hit.t - 4.5).abs() < 1e-10);
+        assert!((hit.normal - Vec3::new(-1.0, 1.0, 0.0).unit()).length() < 1e-10);
+        // The top row of the image is at the far side in -z, where v is 1
+        assert!((hit.u - 0.25).abs() < 1e-10);
+        assert!((hit.v - 0.15).abs() < 1e-10);
+
+        assert!(heightfield
+            .hit(
+                &Ray::new(Point3::new(1.5, 5.0, 0.0), down, 0.0),
+                0.001,
+                f64::INFINITY
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn test_grid_traversal() {
+        let heightfield = ridge();
+        // Triangles of the same surface, tested one by one
+        let triangles = (0..2)
+            .flat_map(|row| (0..2).map(move |column| (column, row)))
+            .flat_map(|(column, row)| {
+                let p00 = heightfield.vertex(column, row);
+                let p10 = heightfield.vertex(column + 1, row);
+                let p01 = heightfield.vertex(column, row + 1);
+                let p11 = heightfield.vertex(column + 1, row + 1);
+                [
+                    Triangle::new(
+                        p00,
+                        p01,
+                        p10,
+                        Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+                    ),
+                    Triangle::new(
+                        p11,
+                        p10,
+                        p01,
+                        Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+                    ),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        for (origin, direction) in [
+            (Point3::new(-3.0, 0.6, -0.8), Vec3::new(1.0, 0.0, 0.3)),
+            (Point3::new(3.0, 0.3, 0.9), Vec3::new(-1.0, 0.05, -0.4)),
+            (Point3::new(0.2, 4.0, 3.0), Vec3::new(-0.1, -1.0, -1.0)),
+            (Point3::new(-0.9, 2.0, -0.9), Vec3::new(0.2, -0.1, 0.2)),
+            (Point3::new(-3.0, 1.5, 0.0), Vec3::new(1.0, 0.0, 0.0)),
+        ] {
+            let ray = Ray::new(origin, direction, 0.0);
+            let expected = triangles
+                .iter()
+                .filter_map(|triangle| triangle.hit(&ray, 0.001, f64::INFINITY))
+                .map(|hit| hit.t)
+                .min_by(f64::total_cmp);
+            let actual = heightfield.hit(&ray, 0.001, f64::INFINITY).map(|hit| hit.t);
+            assert_eq!(expected.is_some(), actual.is_some());
+            if let (Some(expected), Some(actual)) = (expected, actual) {
+                assert!((expected - actual).abs() < 1e-10);
+            }
+        }
+    }
+}
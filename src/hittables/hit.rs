@@ -15,6 +15,8 @@ pub struct Hit<'a> {
     /// True if hitting ray is outgoing to surface
     pub front_face: bool,
     pub material: &'a dyn Material,
+    /// Vertex indices and barycentric weights of the point hit on a `TriangleMesh` face
+    pub vertices: Option<([usize; 3], [f64; 3])>,
 }
 
 impl<'a> Hit<'a> {
@@ -36,6 +38,7 @@ impl<'a> Hit<'a> {
             v,
             front_face,
             material,
+            vertices: None,
         }
     }
 
@@ -61,6 +64,7 @@ impl<'a> Hit<'a> {
             v,
             front_face,
             material,
+            vertices: None,
         }
     }
 
@@ -1,8 +1,165 @@
-use crate::ray::Ray;
+use std::sync::Arc;
+
+use crate::{
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
 
 use super::{hit::Hit, Aabb};
 
 pub trait Hittable: Send + Sync {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit>;
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>>;
     fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb>;
+
+    /// Density over solid angle with which `random` picks `direction` from `origin`.
+    ///
+    /// Objects that cannot be sampled as lights return zero.
+    #[allow(unused_variables)]
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        0.0
+    }
+
+    /// Random direction from `origin` toward the object, for sampling it as a light.
+    #[allow(unused_variables)]
+    fn random(&self, origin: Point3) -> Vec3 {
+        Vec3::new(1.0, 0.0, 0.0)
+    }
+
+    /// Surface area of objects that can be sampled as lights, zero for the others.
+    fn area(&self) -> f64 {
+        0.0
+    }
+}
+
+/// Density over solid angle of a point sampled uniformly on a surface of `area`, seen from the
+/// origin of `direction` through `hit`.
+pub(super) fn area_pdf(hit: Option<Hit>, direction: Vec3, area: f64) -> f64 {
+    let Some(hit) = hit else {
+        return 0.0;
+    };
+    let distance_squared = hit.t * hit.t * direction.length_squared();
+    let cosine = (direction.dot(hit.normal) / direction.length()).abs();
+    if cosine == 0.0 {
+        return 0.0;
+    }
+    distance_squared / (cosine * area)
+}
+
+impl<H: Hittable + ?Sized> Hittable for Box<H> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
+        (**self).hit(ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        (**self).bounding_box(time0, time1)
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        (**self).pdf_value(origin, direction)
+    }
+
+    fn random(&self, origin: Point3) -> Vec3 {
+        (**self).random(origin)
+    }
+
+    fn area(&self) -> f64 {
+        (**self).area()
+    }
+}
+
+impl<H: Hittable + ?Sized> Hittable for Arc<H> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
+        (**self).hit(ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        (**self).bounding_box(time0, time1)
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        (**self).pdf_value(origin, direction)
+    }
+
+    fn random(&self, origin: Point3) -> Vec3 {
+        (**self).random(origin)
+    }
+
+    fn area(&self) -> f64 {
+        (**self).area()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::{
+        hittables::{HittableVec, Quad, Sphere, Triangle, XzRect},
+        materials::Lambertian,
+        random,
+        vec3::Color,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_pdf_integrates_to_one() {
+        let objects: HittableVec = vec![
+            Box::new(Sphere::new(
+                Point3::new(0.0, 3.0, 0.0),
+                1.0,
+                Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+            )),
+            Box::new(XzRect::new(
+                -1.0,
+                2.0,
+                -1.0,
+                1.0,
+                2.0,
+                Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+            )),
+            Box::new(Quad::new(
+                Point3::new(1.0, -1.0, -1.0),
+                Vec3::new(0.0, 2.0, 0.0),
+                Vec3::new(1.0, 0.0, 2.0),
+                Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+            )),
+            Box::new(Triangle::new(
+                Point3::new(-1.0, -1.0, 1.0),
+                Point3::new(-1.0, 1.0, 2.0),
+                Point3::new(-2.0, 0.0, 1.0),
+                Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+            )),
+        ];
+        let origin = Point3::new(0.0, 0.0, 0.0);
+        random::seed(1);
+
+        // Estimated with directions picked uniformly over the sphere
+        let n = 200_000;
+        let directions = (0..n)
+            .map(|_| Vec3::random_in_unit_sphere().unit())
+            .collect::<Vec<_>>();
+        let integral = |object: &dyn Hittable| {
+            directions
+                .iter()
+                .map(|&direction| object.pdf_value(origin, direction))
+                .sum::<f64>()
+                * 4.0
+                * PI
+                / n as f64
+        };
+        for object in objects.iter() {
+            assert!((integral(object) - 1.0).abs() < 0.02);
+        }
+        assert!((integral(&objects) - 1.0).abs() < 0.02);
+
+        // Sampled directions reach the objects
+        for _ in 0..1000 {
+            let direction = objects.random(origin);
+            assert!(objects.pdf_value(origin, direction) > 0.0);
+            assert!(objects
+                .hit(&Ray::new(origin, direction, 0.0), 0.001, f64::INFINITY)
+                .is_some());
+        }
+    }
 }
@@ -1,11 +1,17 @@
-use crate::ray::Ray;
+use rand::Rng;
+
+use crate::{
+    random,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
 
 use super::{hit::Hit, Aabb, Hittable};
 
 pub type HittableVec = Vec<Box<dyn Hittable>>;
 
 impl Hittable for HittableVec {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
         let mut t_max = t_max;
         let mut record: Option<Hit> = None;
         for hittable in self.iter() {
@@ -24,7 +30,9 @@ impl Hittable for HittableVec {
 
         let mut result: Option<Aabb> = None;
         for object in self.iter() {
-            let Some(b) = object.bounding_box(time0, time1) else { continue; };
+            let Some(b) = object.bounding_box(time0, time1) else {
+                continue;
+            };
             if let Some(r) = result {
                 result = Some(r.union(&b))
             } else {
@@ -34,4 +42,27 @@ impl Hittable for HittableVec {
 
         result
     }
+
+    /// Average of the densities of the objects, each of which is sampled equally often.
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        let sum: f64 = self
+            .iter()
+            .map(|object| object.pdf_value(origin, direction))
+            .sum();
+        sum / self.len() as f64
+    }
+
+    fn random(&self, origin: Point3) -> Vec3 {
+        if self.is_empty() {
+            return Vec3::new(1.0, 0.0, 0.0);
+        }
+        self[random::rng().gen_range(0..self.len())].random(origin)
+    }
+
+    fn area(&self) -> f64 {
+        self.iter().map(|object| object.area()).sum()
+    }
 }
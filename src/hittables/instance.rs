@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use crate::{materials::Material, matrix::Matrix4, ray::Ray};
+
+use super::{Aabb, Hit, Hittable, Transform};
+
+/// Placement of a shared object with its own transform and optionally its own material.
+///
+/// Instances refer to the object through an `Arc`, so a bottom-level `BvhTree` of a large mesh
+/// can be placed many times without copying it.
+pub struct Instance<H: Hittable + ?Sized> {
+    transform: Transform<Arc<H>>,
+    material: Option<Arc<dyn Material>>,
+}
+
+impl<H: Hittable + ?Sized> Instance<H> {
+    /// Panics if `transform` is not invertible.
+    pub fn new(object: Arc<H>, transform: Matrix4) -> Instance<H> {
+        Instance {
+            transform: Transform::new(object, transform),
+            material: None,
+        }
+    }
+
+    /// Same as `new`, except that every hit uses `material` instead of that of the object.
+    pub fn new_with_material(
+        object: Arc<H>,
+        transform: Matrix4,
+        material: Arc<dyn Material>,
+    ) -> Instance<H> {
+        Instance {
+            material: Some(material),
+            ..Instance::new(object, transform)
+        }
+    }
+}
+
+impl<H: Hittable + ?Sized> Hittable for Instance<H> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
+        let mut hit = self.transform.hit(ray, t_min, t_max)?;
+        if let Some(material) = &self.material {
+            hit.material = material.as_ref();
+        }
+        Some(hit)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        self.transform.bounding_box(time0, time1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hittables::{BvhTree, Sphere},
+        materials::{Lambertian, Metal},
+        vec3::{Color, Point3, Vec3},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_shared_object() {
+        let sphere = Sphere::new(
+            Point3::new(0.0, 0.0, 0.0),
+            1.0,
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        );
+        let object = Arc::new(BvhTree::new(vec![Box::new(sphere)], 0.0, 1.0));
+        let left = Instance::new(
+            object.clone(),
+            Matrix4::translation(Vec3::new(-3.0, 0.0, 0.0)),
+        );
+        let right = Instance::new_with_material(
+            object.clone(),
+            Matrix4::translation(Vec3::new(3.0, 0.0, 0.0))
+                * Matrix4::scaling(Vec3::new(2.0, 1.0, 1.0)),
+            Arc::new(Metal::new(Color::new(0.8, 0.8, 0.8), 0.0)),
+        );
+        assert_eq!(3, Arc::strong_count(&object));
+
+        let ray = Ray::new(Point3::new(-3.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+        let hit = left.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 4.0).abs() < 1e-10);
+        assert!((hit.p - Point3::new(-3.0, 0.0, -1.0)).length() < 1e-10);
+        assert!(right.hit(&ray, 0.001, f64::INFINITY).is_none());
+
+        // The sphere is stretched along x, so its normal tilts away from the direction of scaling
+        let ray = Ray::new(Point3::new(4.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+        let hit = right.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        let expected_z = -(1.0 - 0.25f64).sqrt();
+        assert!((hit.p - Point3::new(4.0, 0.0, expected_z)).length() < 1e-10);
+        let expected_normal = Vec3::new(0.5 / 2.0, 0.0, expected_z).unit();
+        assert!((hit.normal - expected_normal).length() < 1e-10);
+        assert!(hit.front_face);
+        let scatter = hit.material.scatter(&ray, &hit).unwrap();
+        assert_eq!(Color::new(0.8, 0.8, 0.8), scatter.attenuation);
+
+        assert_eq!(
+            Aabb::new(Point3::new(1.0, -1.0, -1.0), Point3::new(5.0, 1.0, 1.0)),
+            round(right.bounding_box(0.0, 1.0).unwrap())
+        );
+    }
+
+    fn round(aabb: Aabb) -> Aabb {
+        let round = |p: Point3| Point3::new(p.x().round(), p.y().round(), p.z().round());
+        Aabb::new(round(aabb.min), round(aabb.max))
+    }
+}
@@ -43,7 +43,7 @@ impl<M: Material> MovingSphere<M> {
 }
 
 impl<M: Material> Hittable for MovingSphere<M> {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
         let oc = ray.origin - self.center_at(ray.time);
         let a = ray.direction.length_squared();
         let half_b = oc.dot(ray.direction);
@@ -0,0 +1,136 @@
+use crate::{
+    materials::Material,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{Aabb, Hit, Hittable};
+
+/// Infinite plane through `point`, facing `normal`.
+///
+/// It has no bounding box, so a `BvhTree` tests it against every ray. The texture coordinates
+/// repeat every unit along two directions on the plane.
+#[derive(Debug, Clone)]
+pub struct Plane<M: Material> {
+    point: Point3,
+    normal: Vec3,
+    tangent: Vec3,
+    bitangent: Vec3,
+    material: M,
+}
+
+impl<M: Material> Plane<M> {
+    /// Panics if `normal` is zero.
+    pub fn new(point: Point3, normal: Vec3, material: M) -> Plane<M> {
+        assert!(!normal.is_near_zero(), "the normal must not be zero");
+        let normal = normal.unit();
+        // Any axis far from parallel to the normal gives a tangent
+        let axis = if normal.x().abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let bitangent = normal.cross(axis).unit();
+        let tangent = bitangent.cross(normal);
+        Plane {
+            point,
+            normal,
+            tangent,
+            bitangent,
+            material,
+        }
+    }
+}
+
+impl<M: Material> Hittable for Plane<M> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
+        let denominator = self.normal.dot(ray.direction);
+        // Parallel to the plane
+        if denominator.abs() < 1e-8 {
+            return None;
+        }
+        let t = self.normal.dot(self.point - ray.origin) / denominator;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        let planar = ray.at(t) - self.point;
+        let u = planar.dot(self.tangent).rem_euclid(1.0);
+        let v = planar.dot(self.bitangent).rem_euclid(1.0);
+        Some(Hit::new_with_outward_normal(
+            ray,
+            t,
+            u,
+            v,
+            self.normal,
+            &self.material,
+        ))
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hittables::{BvhTree, Sphere},
+        materials::Lambertian,
+        vec3::Color,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_hit() {
+        let plane = Plane::new(
+            Point3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, 2.0, 0.0),
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        );
+
+        let ray = Ray::new(
+            Point3::new(100.0, 4.0, -50.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            0.0,
+        );
+        let hit = plane.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 5.0).abs() < 1e-10);
+        assert!(hit.front_face);
+        assert!((0.0..1.0).contains(&hit.u) && (0.0..1.0).contains(&hit.v));
+
+        let parallel = Ray::new(Point3::new(0.0, 4.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        assert!(plane.hit(&parallel, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn test_in_bvh_tree() {
+        let tree = BvhTree::new(
+            vec![
+                Box::new(Plane::new(
+                    Point3::new(0.0, -1.0, 0.0),
+                    Vec3::new(0.0, 1.0, 0.0),
+                    Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+                )),
+                Box::new(Sphere::new(
+                    Point3::new(0.0, 0.0, 0.0),
+                    0.5,
+                    Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+                )),
+            ],
+            0.0,
+            1.0,
+        );
+        assert!(tree.bounding_box(0.0, 1.0).is_none());
+
+        let down = Vec3::new(0.0, -1.0, 0.0);
+        let hit = |x: f64| {
+            let ray = Ray::new(Point3::new(x, 5.0, 0.0), down, 0.0);
+            tree.hit(&ray, 0.001, f64::INFINITY).unwrap().t
+        };
+        // The sphere in front of the plane, and the plane beside it
+        assert!((hit(0.0) - 4.5).abs() < 1e-10);
+        assert!((hit(3.0) - 6.0).abs() < 1e-10);
+    }
+}
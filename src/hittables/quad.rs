@@ -0,0 +1,166 @@
+use rand::Rng;
+
+use crate::{
+    materials::Material,
+    random,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{hittable::area_pdf, Aabb, Hit, Hittable};
+
+/// Parallelogram spanned by the edges `u` and `v` from the corner `q`, in any orientation.
+///
+/// The front faces the direction of `u × v`, and the texture coordinates run from 0 to 1 along
+/// both edges.
+#[derive(Debug, Clone)]
+pub struct Quad<M: Material> {
+    q: Point3,
+    u: Vec3,
+    v: Vec3,
+    normal: Vec3,
+    /// `normal · p` of every point `p` on the plane
+    d: f64,
+    /// `n / (n · n)` with `n = u × v`, which turns cross products into coordinates along the edges
+    w: Vec3,
+    material: M,
+}
+
+impl<M: Material> Quad<M> {
+    /// Panics if `u` and `v` are parallel.
+    pub fn new(q: Point3, u: Vec3, v: Vec3, material: M) -> Quad<M> {
+        let n = u.cross(v);
+        assert!(!n.is_near_zero(), "the edges must not be parallel");
+        let normal = n.unit();
+        Quad {
+            q,
+            u,
+            v,
+            normal,
+            d: normal.dot(q),
+            w: n / n.dot(n),
+            material,
+        }
+    }
+}
+
+impl<M: Material> Hittable for Quad<M> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
+        let denominator = self.normal.dot(ray.direction);
+        // Parallel to the plane
+        if denominator.abs() < 1e-8 {
+            return None;
+        }
+        let t = (self.d - self.normal.dot(ray.origin)) / denominator;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        let planar = ray.at(t) - self.q;
+        let alpha = self.w.dot(planar.cross(self.v));
+        let beta = self.w.dot(self.u.cross(planar));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+
+        Some(Hit::new_with_outward_normal(
+            ray,
+            t,
+            alpha,
+            beta,
+            self.normal,
+            &self.material,
+        ))
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
+        let aabb = Aabb::new(self.q, self.q + self.u + self.v)
+            .union(&Aabb::new(self.q + self.u, self.q + self.v));
+        // Pads the axes along which the quad is flat
+        let mut min = aabb.min;
+        let mut max = aabb.max;
+        for axis in 0..3 {
+            if max[axis] - min[axis] < 0.0002 {
+                min[axis] -= 0.0001;
+                max[axis] += 0.0001;
+            }
+        }
+        Some(Aabb::new(min, max))
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let hit = self.hit(&Ray::new(origin, direction, 0.0), 0.001, f64::INFINITY);
+        area_pdf(hit, direction, self.area())
+    }
+
+    fn random(&self, origin: Point3) -> Vec3 {
+        let mut rng = random::rng();
+        self.q + rng.gen::<f64>() * self.u + rng.gen::<f64>() * self.v - origin
+    }
+
+    fn area(&self) -> f64 {
+        self.u.cross(self.v).length()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{hittables::XyRect, materials::Lambertian, vec3::Color};
+
+    use super::*;
+
+    #[test]
+    fn test_matches_rect() {
+        let quad = Quad::new(
+            Point3::new(1.0, 2.0, 3.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 4.0, 0.0),
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        );
+        let rect = XyRect::new(
+            1.0,
+            3.0,
+            2.0,
+            6.0,
+            3.0,
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        );
+
+        for (x, y) in [(1.5, 2.5), (2.9, 5.9), (0.9, 3.0), (2.0, 6.1)] {
+            let ray = Ray::new(Point3::new(x, y, -1.0), Vec3::new(0.1, 0.2, 1.0), 0.0);
+            let expected = rect.hit(&ray, 0.001, f64::INFINITY);
+            let actual = quad.hit(&ray, 0.001, f64::INFINITY);
+            assert_eq!(expected.is_some(), actual.is_some());
+            if let (Some(expected), Some(actual)) = (expected, actual) {
+                assert!((expected.t - actual.t).abs() < 1e-10);
+                assert!((expected.u - actual.u).abs() < 1e-10);
+                assert!((expected.v - actual.v).abs() < 1e-10);
+                assert!((expected.normal - actual.normal).length() < 1e-10);
+                assert_eq!(expected.front_face, actual.front_face);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tilted() {
+        // A unit square tilted by 45 degrees around the x axis
+        let quad = Quad::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 1.0).unit(),
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        );
+
+        let ray = Ray::new(Point3::new(0.5, 0.5, -5.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+        let hit = quad.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.p - Point3::new(0.5, 0.5, 0.5)).length() < 1e-10);
+        assert!((hit.v - 0.5f64.sqrt()).abs() < 1e-10);
+        // The ray hits the back, since the front faces u × v = (0, -1, 1)
+        assert!((hit.normal - Vec3::new(0.0, 1.0, -1.0).unit()).length() < 1e-10);
+        assert!(!hit.front_face);
+
+        let aabb = quad.bounding_box(0.0, 1.0).unwrap();
+        let corner = 0.5f64.sqrt();
+        assert!((aabb.max - Point3::new(1.0, corner, corner)).length() < 1e-10);
+    }
+}
@@ -0,0 +1,475 @@
+use std::f64::consts::PI;
+
+use crate::{
+    materials::Material,
+    polynomial::solve_quadratic,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{Aabb, Hit, Hittable};
+
+/// Disk in the xz plane facing +y, optionally with a hole in the middle.
+#[derive(Debug, Clone)]
+pub struct Disk<M: Material> {
+    center: Point3,
+    radius: f64,
+    inner_radius: f64,
+    material: M,
+}
+
+impl<M: Material> Disk<M> {
+    pub fn new(center: Point3, radius: f64, material: M) -> Disk<M> {
+        Disk::new_with_inner_radius(center, radius, 0.0, material)
+    }
+
+    /// Annulus between `inner_radius` and `radius`.
+    pub fn new_with_inner_radius(
+        center: Point3,
+        radius: f64,
+        inner_radius: f64,
+        material: M,
+    ) -> Disk<M> {
+        assert!(0.0 <= inner_radius && inner_radius < radius);
+        Disk {
+            center,
+            radius,
+            inner_radius,
+            material,
+        }
+    }
+}
+
+impl<M: Material> Hittable for Disk<M> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
+        let o = ray.origin - self.center;
+        let t = plane(o, ray.direction, 0.0, t_min, t_max)?;
+        let p = o + t * ray.direction;
+        let r = (p.x() * p.x() + p.z() * p.z()).sqrt();
+        if r < self.inner_radius || r > self.radius {
+            return None;
+        }
+        let u = azimuth(p);
+        let v = (r - self.inner_radius) / (self.radius - self.inner_radius);
+        Some(Hit::new_with_outward_normal(
+            ray,
+            t,
+            u,
+            v,
+            Vec3::new(0.0, 1.0, 0.0),
+            &self.material,
+        ))
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
+        let extent = Vec3::new(self.radius, 0.0001, self.radius);
+        Some(Aabb::new(self.center - extent, self.center + extent))
+    }
+}
+
+/// Cylinder standing on `base` along +y, closed by disks at both ends unless uncapped.
+#[derive(Debug, Clone)]
+pub struct Cylinder<M: Material> {
+    base: Point3,
+    radius: f64,
+    height: f64,
+    capped: bool,
+    material: M,
+}
+
+impl<M: Material> Cylinder<M> {
+    pub fn new(base: Point3, radius: f64, height: f64, material: M) -> Cylinder<M> {
+        assert!(radius > 0.0 && height > 0.0);
+        Cylinder {
+            base,
+            radius,
+            height,
+            capped: true,
+            material,
+        }
+    }
+
+    /// Only the curved side, open at both ends.
+    pub fn new_uncapped(base: Point3, radius: f64, height: f64, material: M) -> Cylinder<M> {
+        Cylinder {
+            capped: false,
+            ..Cylinder::new(base, radius, height, material)
+        }
+    }
+}
+
+impl<M: Material> Hittable for Cylinder<M> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
+        let (o, d) = (ray.origin - self.base, ray.direction);
+        let roots = solve_quadratic(
+            d.x() * d.x() + d.z() * d.z(),
+            2.0 * (o.x() * d.x() + o.z() * d.z()),
+            o.x() * o.x() + o.z() * o.z() - self.radius * self.radius,
+        );
+        let side = nearest(roots, t_min, t_max, |t| {
+            (0.0..=self.height).contains(&(o.y() + t * d.y()))
+        })
+        .map(|t| {
+            let p = o + t * d;
+            let outward_normal = Vec3::new(p.x(), 0.0, p.z()) / self.radius;
+            (t, outward_normal, azimuth(p), p.y() / self.height)
+        });
+
+        let mut nearest_hit = side;
+        if self.capped {
+            for (y, normal_y) in [(0.0, -1.0), (self.height, 1.0)] {
+                let cap = cap(o, d, y, self.radius, t_min, t_max)
+                    .map(|(t, u, v)| (t, Vec3::new(0.0, normal_y, 0.0), u, v));
+                nearest_hit = closer(nearest_hit, cap);
+            }
+        }
+
+        let (t, outward_normal, u, v) = nearest_hit?;
+        Some(Hit::new_with_outward_normal(
+            ray,
+            t,
+            u,
+            v,
+            outward_normal,
+            &self.material,
+        ))
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
+        Some(Aabb::new(
+            self.base - Vec3::new(self.radius, 0.0, self.radius),
+            self.base + Vec3::new(self.radius, self.height, self.radius),
+        ))
+    }
+}
+
+/// Cone standing on `base` along +y with its apex at `height`, closed by a disk at the base
+/// unless uncapped.
+#[derive(Debug, Clone)]
+pub struct Cone<M: Material> {
+    base: Point3,
+    radius: f64,
+    height: f64,
+    capped: bool,
+    material: M,
+}
+
+impl<M: Material> Cone<M> {
+    pub fn new(base: Point3, radius: f64, height: f64, material: M) -> Cone<M> {
+        assert!(radius > 0.0 && height > 0.0);
+        Cone {
+            base,
+            radius,
+            height,
+            capped: true,
+            material,
+        }
+    }
+
+    /// Only the curved side, open at the base.
+    pub fn new_uncapped(base: Point3, radius: f64, height: f64, material: M) -> Cone<M> {
+        Cone {
+            capped: false,
+            ..Cone::new(base, radius, height, material)
+        }
+    }
+}
+
+impl<M: Material> Hittable for Cone<M> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
+        let (o, d) = (ray.origin - self.base, ray.direction);
+        // `x^2 + z^2 = k (height - y)^2`
+        let k = (self.radius / self.height).powi(2);
+        let h = self.height - o.y();
+        let roots = solve_quadratic(
+            d.x() * d.x() + d.z() * d.z() - k * d.y() * d.y(),
+            2.0 * (o.x() * d.x() + o.z() * d.z() + k * h * d.y()),
+            o.x() * o.x() + o.z() * o.z() - k * h * h,
+        );
+        let side = nearest(roots, t_min, t_max, |t| {
+            (0.0..=self.height).contains(&(o.y() + t * d.y()))
+        })
+        .map(|t| {
+            let p = o + t * d;
+            let outward_normal = Vec3::new(p.x(), k * (self.height - p.y()), p.z()).unit();
+            (t, outward_normal, azimuth(p), p.y() / self.height)
+        });
+
+        let cap = if self.capped {
+            cap(o, d, 0.0, self.radius, t_min, t_max)
+                .map(|(t, u, v)| (t, Vec3::new(0.0, -1.0, 0.0), u, v))
+        } else {
+            None
+        };
+
+        let (t, outward_normal, u, v) = closer(side, cap)?;
+        Some(Hit::new_with_outward_normal(
+            ray,
+            t,
+            u,
+            v,
+            outward_normal,
+            &self.material,
+        ))
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
+        Some(Aabb::new(
+            self.base - Vec3::new(self.radius, 0.0, self.radius),
+            self.base + Vec3::new(self.radius, self.height, self.radius),
+        ))
+    }
+}
+
+/// Paraboloid with its vertex at `base`, opening along +y to `radius` at `height`, closed by a
+/// disk at the top unless uncapped.
+#[derive(Debug, Clone)]
+pub struct Paraboloid<M: Material> {
+    base: Point3,
+    radius: f64,
+    height: f64,
+    capped: bool,
+    material: M,
+}
+
+impl<M: Material> Paraboloid<M> {
+    pub fn new(base: Point3, radius: f64, height: f64, material: M) -> Paraboloid<M> {
+        assert!(radius > 0.0 && height > 0.0);
+        Paraboloid {
+            base,
+            radius,
+            height,
+            capped: true,
+            material,
+        }
+    }
+
+    /// Only the curved surface, open at the top like a bowl.
+    pub fn new_uncapped(base: Point3, radius: f64, height: f64, material: M) -> Paraboloid<M> {
+        Paraboloid {
+            capped: false,
+            ..Paraboloid::new(base, radius, height, material)
+        }
+    }
+}
+
+impl<M: Material> Hittable for Paraboloid<M> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
+        let (o, d) = (ray.origin - self.base, ray.direction);
+        // `x^2 + z^2 = k y`
+        let k = self.radius * self.radius / self.height;
+        let roots = solve_quadratic(
+            d.x() * d.x() + d.z() * d.z(),
+            2.0 * (o.x() * d.x() + o.z() * d.z()) - k * d.y(),
+            o.x() * o.x() + o.z() * o.z() - k * o.y(),
+        );
+        let side = nearest(roots, t_min, t_max, |t| o.y() + t * d.y() <= self.height).map(|t| {
+            let p = o + t * d;
+            let outward_normal = Vec3::new(2.0 * p.x(), -k, 2.0 * p.z()).unit();
+            (t, outward_normal, azimuth(p), p.y() / self.height)
+        });
+
+        let cap = if self.capped {
+            cap(o, d, self.height, self.radius, t_min, t_max)
+                .map(|(t, u, v)| (t, Vec3::new(0.0, 1.0, 0.0), u, v))
+        } else {
+            None
+        };
+
+        let (t, outward_normal, u, v) = closer(side, cap)?;
+        Some(Hit::new_with_outward_normal(
+            ray,
+            t,
+            u,
+            v,
+            outward_normal,
+            &self.material,
+        ))
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
+        Some(Aabb::new(
+            self.base - Vec3::new(self.radius, 0.0, self.radius),
+            self.base + Vec3::new(self.radius, self.height, self.radius),
+        ))
+    }
+}
+
+/// `u` in [0, 1] of the angle around the y axis, with the same origin as `Sphere`.
+pub(super) fn azimuth(p: Vec3) -> f64 {
+    ((-p.z()).atan2(p.x()) + PI) / (2.0 * PI)
+}
+
+/// The smallest root in the range for which `accept` holds.
+fn nearest(roots: Vec<f64>, t_min: f64, t_max: f64, accept: impl Fn(f64) -> bool) -> Option<f64> {
+    roots
+        .into_iter()
+        .filter(|&t| t_min <= t && t <= t_max && accept(t))
+        .min_by(f64::total_cmp)
+}
+
+/// Crossing of the plane at height `y` relative to the origin of a shape.
+fn plane(o: Vec3, d: Vec3, y: f64, t_min: f64, t_max: f64) -> Option<f64> {
+    let t = (y - o.y()) / d.y();
+    (t_min <= t && t <= t_max).then_some(t)
+}
+
+/// Hit of a disk cap at height `y`, with `u` around the axis and `v` from the center.
+fn cap(o: Vec3, d: Vec3, y: f64, radius: f64, t_min: f64, t_max: f64) -> Option<(f64, f64, f64)> {
+    let t = plane(o, d, y, t_min, t_max)?;
+    let p = o + t * d;
+    let r = (p.x() * p.x() + p.z() * p.z()).sqrt();
+    (r <= radius).then(|| (t, azimuth(p), r / radius))
+}
+
+fn closer<T>(
+    a: Option<(f64, T, f64, f64)>,
+    b: Option<(f64, T, f64, f64)>,
+) -> Option<(f64, T, f64, f64)> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(if b.0 < a.0 { b } else { a }),
+        (a, b) => a.or(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{materials::Lambertian, vec3::Color};
+
+    use super::*;
+
+    fn hit(hittable: &impl Hittable, origin: Point3, direction: Vec3) -> Option<Hit<'_>> {
+        hittable.hit(&Ray::new(origin, direction, 0.0), 0.001, f64::INFINITY)
+    }
+
+    fn assert_near(expected: Vec3, actual: Vec3) {
+        assert!(
+            (expected - actual).length() < 1e-10,
+            "expected {expected}, found {actual}"
+        );
+    }
+
+    #[test]
+    fn test_disk() {
+        let disk = Disk::new_with_inner_radius(
+            Point3::new(0.0, 1.0, 0.0),
+            2.0,
+            1.0,
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        );
+        let down = Vec3::new(0.0, -1.0, 0.0);
+
+        let h = hit(&disk, Point3::new(1.5, 5.0, 0.0), down).unwrap();
+        assert!((h.t - 4.0).abs() < 1e-10);
+        assert!(h.front_face);
+        assert!((h.v - 0.5).abs() < 1e-10);
+        assert!(hit(&disk, Point3::new(0.5, 5.0, 0.0), down).is_none());
+        assert!(hit(&disk, Point3::new(2.5, 5.0, 0.0), down).is_none());
+    }
+
+    #[test]
+    fn test_cylinder() {
+        let cylinder = Cylinder::new(
+            Point3::new(0.0, 0.0, 0.0),
+            1.0,
+            2.0,
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        );
+
+        let h = hit(
+            &cylinder,
+            Point3::new(-5.0, 0.5, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+        )
+        .unwrap();
+        assert_near(Point3::new(-1.0, 0.5, 0.0), h.p);
+        assert_near(Vec3::new(-1.0, 0.0, 0.0), h.normal);
+        assert!((h.v - 0.25).abs() < 1e-10);
+
+        // The top cap, and nothing through the open top without caps
+        let down = Vec3::new(0.0, -1.0, 0.0);
+        let h = hit(&cylinder, Point3::new(0.5, 5.0, 0.0), down).unwrap();
+        assert!((h.t - 3.0).abs() < 1e-10);
+        assert!(h.front_face);
+        let uncapped = Cylinder::new_uncapped(
+            Point3::new(0.0, 0.0, 0.0),
+            1.0,
+            2.0,
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        );
+        assert!(hit(&uncapped, Point3::new(0.5, 5.0, 0.0), down).is_none());
+
+        // From inside the uncapped tube, the wall is hit from behind
+        let h = hit(
+            &uncapped,
+            Point3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        )
+        .unwrap();
+        assert!((h.t - 1.0).abs() < 1e-10);
+        assert!(!h.front_face);
+    }
+
+    #[test]
+    fn test_cone() {
+        let cone = Cone::new(
+            Point3::new(0.0, 0.0, 0.0),
+            1.0,
+            1.0,
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        );
+
+        // Halfway up, the radius is half
+        let h = hit(&cone, Point3::new(-5.0, 0.5, 0.0), Vec3::new(1.0, 0.0, 0.0)).unwrap();
+        assert_near(Point3::new(-0.5, 0.5, 0.0), h.p);
+        assert_near(Vec3::new(-1.0, 1.0, 0.0).unit(), h.normal);
+
+        let h = hit(
+            &cone,
+            Point3::new(0.75, -5.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+        .unwrap();
+        assert!((h.t - 5.0).abs() < 1e-10);
+        assert_near(Vec3::new(0.0, -1.0, 0.0), h.normal);
+
+        // Past the apex, the other nappe of the double cone is not part of the shape
+        assert!(hit(&cone, Point3::new(-5.0, 1.5, 0.0), Vec3::new(1.0, 0.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn test_paraboloid() {
+        let paraboloid = Paraboloid::new_uncapped(
+            Point3::new(0.0, 0.0, 0.0),
+            2.0,
+            4.0,
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        );
+
+        let h = hit(
+            &paraboloid,
+            Point3::new(1.0, -5.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+        .unwrap();
+        assert_near(Point3::new(1.0, 1.0, 0.0), h.p);
+        assert!(h.front_face);
+        assert_near(Vec3::new(2.0, -1.0, 0.0).unit(), h.normal);
+
+        // Into the open bowl from above, the inside is hit
+        let h = hit(
+            &paraboloid,
+            Point3::new(1.0, 5.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0),
+        )
+        .unwrap();
+        assert_near(Point3::new(1.0, 1.0, 0.0), h.p);
+        assert!(!h.front_face);
+
+        let aabb = paraboloid.bounding_box(0.0, 1.0).unwrap();
+        assert_eq!(
+            Aabb::new(Point3::new(-2.0, 0.0, -2.0), Point3::new(2.0, 4.0, 2.0)),
+            aabb
+        );
+    }
+}
@@ -1,10 +1,13 @@
+use rand::Rng;
+
 use crate::{
     materials::Material,
+    random,
     ray::Ray,
     vec3::{Point3, Vec3},
 };
 
-use super::{Aabb, Hit, Hittable};
+use super::{hittable::area_pdf, Aabb, Hit, Hittable};
 
 #[derive(Debug, Clone)]
 pub struct XyRect<M: Material> {
@@ -32,7 +35,7 @@ impl<M: Material> XyRect<M> {
 }
 
 impl<M: Material> Hittable for XyRect<M> {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
         let t = (self.z - ray.origin.z()) / ray.direction.z();
         if t < t_min || t > t_max {
             return None;
@@ -60,6 +63,25 @@ impl<M: Material> Hittable for XyRect<M> {
         let max = Point3::new(self.x1, self.y1, self.z + 0.0001);
         Some(Aabb::new(min, max))
     }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let hit = self.hit(&Ray::new(origin, direction, 0.0), 0.001, f64::INFINITY);
+        area_pdf(hit, direction, self.area())
+    }
+
+    fn random(&self, origin: Point3) -> Vec3 {
+        let mut rng = random::rng();
+        let p = Point3::new(
+            rng.gen_range(self.x0..self.x1),
+            rng.gen_range(self.y0..self.y1),
+            self.z,
+        );
+        p - origin
+    }
+
+    fn area(&self) -> f64 {
+        (self.x1 - self.x0) * (self.y1 - self.y0)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -88,7 +110,7 @@ impl<M: Material> XzRect<M> {
 }
 
 impl<M: Material> Hittable for XzRect<M> {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
         let t = (self.y - ray.origin.y()) / ray.direction.y();
         if t < t_min || t > t_max {
             return None;
@@ -116,6 +138,25 @@ impl<M: Material> Hittable for XzRect<M> {
         let max = Point3::new(self.x1, self.y + 0.0001, self.z1);
         Some(Aabb::new(min, max))
     }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let hit = self.hit(&Ray::new(origin, direction, 0.0), 0.001, f64::INFINITY);
+        area_pdf(hit, direction, self.area())
+    }
+
+    fn random(&self, origin: Point3) -> Vec3 {
+        let mut rng = random::rng();
+        let p = Point3::new(
+            rng.gen_range(self.x0..self.x1),
+            self.y,
+            rng.gen_range(self.z0..self.z1),
+        );
+        p - origin
+    }
+
+    fn area(&self) -> f64 {
+        (self.x1 - self.x0) * (self.z1 - self.z0)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -144,7 +185,7 @@ impl<M: Material> YzRect<M> {
 }
 
 impl<M: Material> Hittable for YzRect<M> {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
         let t = (self.x - ray.origin.x()) / ray.direction.x();
         if t < t_min || t > t_max {
             return None;
@@ -172,4 +213,23 @@ impl<M: Material> Hittable for YzRect<M> {
         let max = Point3::new(self.x + 0.0001, self.y1, self.z1);
         Some(Aabb::new(min, max))
     }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let hit = self.hit(&Ray::new(origin, direction, 0.0), 0.001, f64::INFINITY);
+        area_pdf(hit, direction, self.area())
+    }
+
+    fn random(&self, origin: Point3) -> Vec3 {
+        let mut rng = random::rng();
+        let p = Point3::new(
+            self.x,
+            rng.gen_range(self.y0..self.y1),
+            rng.gen_range(self.z0..self.z1),
+        );
+        p - origin
+    }
+
+    fn area(&self) -> f64 {
+        (self.y1 - self.y0) * (self.z1 - self.z0)
+    }
 }
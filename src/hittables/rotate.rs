@@ -1,60 +1,28 @@
-use crate::{ray::Ray, vec3::Point3};
+use crate::{matrix::Matrix4, ray::Ray, vec3::Vec3};
 
 use super::{Aabb, Hit, Hittable};
 
 #[derive(Debug, Clone)]
 pub struct RotateY<H: Hittable> {
     hittable: H,
+    theta: f64,
     sin_theta: f64,
     cos_theta: f64,
-    bbox: Option<Aabb>,
 }
 
 impl<H: Hittable> RotateY<H> {
     pub fn new(hittable: H, theta: f64) -> RotateY<H> {
-        let sin_theta = theta.sin();
-        let cos_theta = theta.cos();
-        let Some(bbox) = hittable.bounding_box(0.0, 1.0) else {
-            return RotateY {
-                hittable,
-                sin_theta,
-                cos_theta,
-                bbox: None,
-            }
-        };
-
-        let mut min = Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY);
-        let mut max = Point3::new(-f64::INFINITY, -f64::INFINITY, -f64::INFINITY);
-
-        for j in 0..2 {
-            let y = j as f64 * bbox.max.y() + (1 - j) as f64 * bbox.min.y();
-            min[1] = min[1].min(y);
-            max[1] = max[1].max(y);
-            for i in 0..2 {
-                for k in 0..2 {
-                    let x = i as f64 * bbox.max.x() + (1 - i) as f64 * bbox.min.x();
-                    let z = k as f64 * bbox.max.z() + (1 - k) as f64 * bbox.min.z();
-                    let new_x = cos_theta * x + sin_theta * z;
-                    let new_z = -sin_theta * x + cos_theta * z;
-                    min[0] = min[0].min(new_x);
-                    max[0] = max[0].max(new_x);
-                    min[2] = min[2].min(new_z);
-                    max[2] = max[2].max(new_z);
-                }
-            }
-        }
-
         RotateY {
             hittable,
-            sin_theta,
-            cos_theta,
-            bbox: Some(Aabb::new(min, max)),
+            theta,
+            sin_theta: theta.sin(),
+            cos_theta: theta.cos(),
         }
     }
 }
 
 impl<H: Hittable> Hittable for RotateY<H> {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
         let mut origin = ray.origin;
         let mut direction = ray.direction;
 
@@ -65,9 +33,7 @@ impl<H: Hittable> Hittable for RotateY<H> {
         direction[2] = self.sin_theta * ray.direction.x() + self.cos_theta * ray.direction.z();
 
         let rotated_ray = Ray::new(origin, direction, ray.time);
-        let Some(mut hit) = self.hittable.hit(&rotated_ray, t_min, t_max) else {
-            return None;
-        };
+        let mut hit = self.hittable.hit(&rotated_ray, t_min, t_max)?;
 
         let mut p = hit.p;
         let mut normal = hit.normal;
@@ -84,8 +50,37 @@ impl<H: Hittable> Hittable for RotateY<H> {
         Some(hit)
     }
 
-    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
-        // FIXME: timeを考慮してない
-        self.bbox
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        let aabb = self.hittable.bounding_box(time0, time1)?;
+        Some(aabb.transform(&Matrix4::rotation(Vec3::new(0.0, 1.0, 0.0), self.theta)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use crate::{hittables::MovingSphere, materials::Lambertian, vec3::Color};
+
+    use super::*;
+
+    #[test]
+    fn test_bounding_box_of_moving_object() {
+        let sphere = MovingSphere::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(4.0, 0.0, 0.0),
+            0.0,
+            2.0,
+            1.0,
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        );
+        let rotated = RotateY::new(sphere, FRAC_PI_2);
+
+        // The sphere moves along x, which becomes -z after the rotation
+        let aabb = rotated.bounding_box(0.0, 2.0).unwrap();
+        assert!((aabb.min - Vec3::new(-1.0, -1.0, -5.0)).length() < 1e-10);
+        assert!((aabb.max - Vec3::new(1.0, 1.0, 1.0)).length() < 1e-10);
+        let aabb = rotated.bounding_box(1.0, 1.0).unwrap();
+        assert!((aabb.min - Vec3::new(-1.0, -1.0, -3.0)).length() < 1e-10);
     }
 }
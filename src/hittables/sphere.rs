@@ -1,7 +1,10 @@
 use std::f64::consts::PI;
 
+use rand::Rng;
+
 use crate::{
     materials::Material,
+    random,
     ray::Ray,
     vec3::{Point3, Vec3},
 };
@@ -47,10 +50,19 @@ impl<M: Material> Sphere<M> {
 
         (phi / (2.0 * PI), theta / PI)
     }
+
+    /// Cosine of the half angle of the cone of directions from `origin` to the sphere, or `None`
+    /// inside the sphere.
+    fn cos_theta_max(&self, origin: Point3) -> Option<f64> {
+        let distance_squared = (self.center - origin).length_squared();
+        let radius_squared = self.radius * self.radius;
+        (distance_squared > radius_squared)
+            .then(|| (1.0 - radius_squared / distance_squared).sqrt())
+    }
 }
 
 impl<M: Material> Hittable for Sphere<M> {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
         let oc = ray.origin - self.center;
         let a = ray.direction.length_squared();
         let half_b = oc.dot(ray.direction);
@@ -84,4 +96,43 @@ impl<M: Material> Hittable for Sphere<M> {
             self.center + Vec3::new(self.radius, self.radius, self.radius),
         ))
     }
+
+    /// Uniform over the cone of directions to the sphere, or over all directions from inside.
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        if self
+            .hit(&Ray::new(origin, direction, 0.0), 0.001, f64::INFINITY)
+            .is_none()
+        {
+            return 0.0;
+        }
+        match self.cos_theta_max(origin) {
+            Some(cos_theta_max) => 1.0 / (2.0 * PI * (1.0 - cos_theta_max)),
+            None => 1.0 / (4.0 * PI),
+        }
+    }
+
+    fn random(&self, origin: Point3) -> Vec3 {
+        let Some(cos_theta_max) = self.cos_theta_max(origin) else {
+            return Vec3::random_in_unit_sphere().unit();
+        };
+        let mut rng = random::rng();
+        let z = 1.0 + rng.gen::<f64>() * (cos_theta_max - 1.0);
+        let phi = 2.0 * PI * rng.gen::<f64>();
+        let r = (1.0 - z * z).sqrt();
+
+        // Around the axis toward the center
+        let w = (self.center - origin).unit();
+        let axis = if w.x().abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let v = w.cross(axis).unit();
+        let u = v.cross(w);
+        r * phi.cos() * u + r * phi.sin() * v + z * w
+    }
+
+    fn area(&self) -> f64 {
+        4.0 * PI * self.radius * self.radius
+    }
 }
@@ -0,0 +1,134 @@
+use std::f64::consts::PI;
+
+use crate::{
+    materials::Material,
+    polynomial::solve_quartic,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{quadric::azimuth, Aabb, Hit, Hittable};
+
+/// Torus around the y axis through `center`, lying in the xz plane.
+#[derive(Debug, Clone)]
+pub struct Torus<M: Material> {
+    center: Point3,
+    /// Distance from the center to the middle of the tube
+    major_radius: f64,
+    /// Radius of the tube
+    minor_radius: f64,
+    material: M,
+}
+
+impl<M: Material> Torus<M> {
+    pub fn new(center: Point3, major_radius: f64, minor_radius: f64, material: M) -> Torus<M> {
+        assert!(0.0 < minor_radius && minor_radius < major_radius);
+        Torus {
+            center,
+            major_radius,
+            minor_radius,
+            material,
+        }
+    }
+}
+
+impl<M: Material> Hittable for Torus<M> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
+        // The quartic is solved from the point of the ray closest to the center with a unit
+        // direction, where its coefficients are small and well-conditioned
+        let length = ray.direction.length();
+        let d = ray.direction / length;
+        let o = ray.origin - self.center;
+        let closest = -o.dot(d);
+        let o = o + closest * d;
+
+        let (r2, rr2) = (
+            self.major_radius * self.major_radius,
+            self.minor_radius * self.minor_radius,
+        );
+        if o.length_squared() > (self.major_radius + self.minor_radius).powi(2) {
+            return None;
+        }
+        // `(|p|^2 + R^2 - r^2)^2 = 4 R^2 (x^2 + z^2)` with `p = o + s d` and `|d| = 1`
+        let e = o.length_squared() + r2 - rr2;
+        let f = o.dot(d);
+        let dxz = d.x() * d.x() + d.z() * d.z();
+        let roots = solve_quartic(
+            1.0,
+            4.0 * f,
+            2.0 * e + 4.0 * f * f - 4.0 * r2 * dxz,
+            4.0 * f * e - 8.0 * r2 * (o.x() * d.x() + o.z() * d.z()),
+            e * e - 4.0 * r2 * (o.x() * o.x() + o.z() * o.z()),
+        );
+        let t = roots
+            .into_iter()
+            .map(|s| (s + closest) / length)
+            .filter(|&t| t_min <= t && t <= t_max)
+            .min_by(f64::total_cmp)?;
+
+        let p = ray.at(t) - self.center;
+        // The normal points away from the nearest point on the circle in the middle of the tube
+        let radial = Vec3::new(p.x(), 0.0, p.z()).unit();
+        let from_ring = p - radial * self.major_radius;
+        let outward_normal = from_ring / self.minor_radius;
+        let u = azimuth(p);
+        let v = (from_ring.y().atan2(from_ring.dot(radial)) + PI) / (2.0 * PI);
+        Some(Hit::new_with_outward_normal(
+            ray,
+            t,
+            u,
+            v,
+            outward_normal,
+            &self.material,
+        ))
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
+        let outer = self.major_radius + self.minor_radius;
+        let extent = Vec3::new(outer, self.minor_radius, outer);
+        Some(Aabb::new(self.center - extent, self.center + extent))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{materials::Lambertian, vec3::Color};
+
+    use super::*;
+
+    #[test]
+    fn test_hit() {
+        let torus = Torus::new(
+            Point3::new(0.0, 1.0, 0.0),
+            2.0,
+            0.5,
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        );
+        let hit = |origin: Point3, direction: Vec3| {
+            torus.hit(&Ray::new(origin, direction, 0.0), 0.001, f64::INFINITY)
+        };
+
+        // Through the hole of the ring
+        assert!(hit(Point3::new(0.0, 10.0, 0.0), Vec3::new(0.0, -1.0, 0.0)).is_none());
+
+        // Along the x axis, the outer side of the tube first
+        let h = hit(Point3::new(-10.0, 1.0, 0.0), Vec3::new(2.0, 0.0, 0.0)).unwrap();
+        assert!((h.t - 3.75).abs() < 1e-9);
+        assert!((h.normal - Vec3::new(-1.0, 0.0, 0.0)).length() < 1e-9);
+        assert!(h.front_face);
+
+        // Down onto the top of the tube
+        let h = hit(Point3::new(0.0, 10.0, 2.0), Vec3::new(0.0, -1.0, 0.0)).unwrap();
+        assert!((h.p - Point3::new(0.0, 1.5, 2.0)).length() < 1e-9);
+        assert!((h.normal - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-9);
+        assert!((h.v - 0.75).abs() < 1e-9);
+
+        // From inside the tube
+        let h = hit(Point3::new(2.0, 1.0, 0.0), Vec3::new(0.0, 1.0, 0.0)).unwrap();
+        assert!((h.t - 0.5).abs() < 1e-9);
+        assert!(!h.front_face);
+
+        // Grazing past the outside of the tube
+        assert!(hit(Point3::new(-10.0, 1.6, 0.0), Vec3::new(1.0, 0.0, 0.0)).is_none());
+    }
+}
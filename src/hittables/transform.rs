@@ -0,0 +1,108 @@
+use crate::{matrix::Matrix4, ray::Ray};
+
+use super::{Aabb, Hit, Hittable};
+
+/// Affine transform of a hittable, such as rotation around any axis, scaling or shearing.
+#[derive(Debug, Clone)]
+pub struct Transform<H: Hittable> {
+    hittable: H,
+    to_world: Matrix4,
+    to_object: Matrix4,
+    normal_to_world: Matrix4,
+}
+
+impl<H: Hittable> Transform<H> {
+    /// Panics if `matrix` is not invertible.
+    pub fn new(hittable: H, matrix: Matrix4) -> Transform<H> {
+        let (Some(to_object), Some(normal_to_world)) = (matrix.inverse(), matrix.normal_matrix())
+        else {
+            panic!("the transform must be invertible");
+        };
+        Transform {
+            hittable,
+            to_world: matrix,
+            to_object,
+            normal_to_world,
+        }
+    }
+}
+
+impl<H: Hittable> Hittable for Transform<H> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
+        // The direction is not normalized, so that `t` is the same in both spaces
+        let object_ray = Ray::new(
+            self.to_object.transform_point(ray.origin),
+            self.to_object.transform_vector(ray.direction),
+            ray.time,
+        );
+        let mut hit = self.hittable.hit(&object_ray, t_min, t_max)?;
+        hit.p = self.to_world.transform_point(hit.p);
+        // The sign of the dot product with the ray direction is kept, and so is `front_face`
+        hit.normal = self.normal_to_world.transform_vector(hit.normal).unit();
+        Some(hit)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        let aabb = self.hittable.bounding_box(time0, time1)?;
+        Some(aabb.transform(&self.to_world))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use crate::{
+        hittables::{Box as HittableBox, XyRect},
+        materials::Lambertian,
+        vec3::{Color, Point3, Vec3},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_rotate_x() {
+        // The rectangle in the xy plane is turned to face +y
+        let rect = XyRect::new(
+            -1.0,
+            1.0,
+            -1.0,
+            1.0,
+            0.0,
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        );
+        let transform = Transform::new(
+            rect,
+            Matrix4::rotation(Vec3::new(1.0, 0.0, 0.0), -FRAC_PI_2),
+        );
+
+        let ray = Ray::new(Point3::new(0.5, 5.0, 0.5), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        let hit = transform.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 5.0).abs() < 1e-10);
+        assert!((hit.normal - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-10);
+
+        let ray = Ray::new(Point3::new(0.5, 0.5, -5.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+        assert!(transform.hit(&ray, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn test_shear() {
+        let unit_box = HittableBox::new(
+            Point3::default(),
+            Point3::new(1.0, 1.0, 1.0),
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        );
+        let transform = Transform::new(unit_box, Matrix4::shearing(1.0, 0.0, 0.0, 0.0, 0.0, 0.0));
+
+        // The right face is slanted, from x = 1 at the bottom to x = 2 at the top
+        let ray = Ray::new(Point3::new(5.0, 0.5, 0.5), Vec3::new(-1.0, 0.0, 0.0), 0.0);
+        let hit = transform.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.p - Point3::new(1.5, 0.5, 0.5)).length() < 1e-10);
+        let expected_normal = Vec3::new(1.0, -1.0, 0.0).unit();
+        assert!((hit.normal - expected_normal).length() < 1e-10);
+
+        let aabb = transform.bounding_box(0.0, 1.0).unwrap();
+        assert!((aabb.min - Point3::default()).length() < 1e-10);
+        assert!((aabb.max - Point3::new(2.0, 1.0, 1.0)).length() < 1e-10);
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{ray::Ray, vec3::Vec3};
+use crate::{
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
 
 use super::{Aabb, Hit, Hittable};
 
@@ -15,23 +18,31 @@ impl<H: Hittable> Translate<H> {
 }
 
 impl<H: Hittable> Hittable for Translate<H> {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
         let moved_ray = Ray::new(ray.origin - self.offset, ray.direction, ray.time);
-        let Some(mut hit) = self.hittable.hit(&moved_ray, t_min, t_max) else {
-            return None;
-        };
+        let mut hit = self.hittable.hit(&moved_ray, t_min, t_max)?;
         hit.p += self.offset;
         hit.set_face_normal(&moved_ray, hit.normal);
         Some(hit)
     }
 
     fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
-        let Some(mut aabb) = self.hittable.bounding_box(time0, time1) else {
-            return None;
-        };
+        let mut aabb = self.hittable.bounding_box(time0, time1)?;
         aabb.min += self.offset;
         aabb.max += self.offset;
 
         Some(aabb)
     }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        self.hittable.pdf_value(origin - self.offset, direction)
+    }
+
+    fn random(&self, origin: Point3) -> Vec3 {
+        self.hittable.random(origin - self.offset)
+    }
+
+    fn area(&self) -> f64 {
+        self.hittable.area()
+    }
 }
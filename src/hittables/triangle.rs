@@ -0,0 +1,176 @@
+use rand::Rng;
+
+use crate::{
+    materials::Material,
+    random,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{hittable::area_pdf, Aabb, Hit, Hittable};
+
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Triangle<M: Material> {
+    pub v0: Point3,
+    pub v1: Point3,
+    pub v2: Point3,
+    pub material: M,
+}
+
+impl<M: Material> Triangle<M> {
+    /// Vertices are expected in counter-clockwise order seen from the front side.
+    pub fn new(v0: Point3, v1: Point3, v2: Point3, material: M) -> Triangle<M> {
+        Triangle {
+            v0,
+            v1,
+            v2,
+            material,
+        }
+    }
+}
+
+/// Möller–Trumbore ray-triangle intersection.
+///
+/// Returns `(t, b1, b2)` where `b1` and `b2` are the barycentric weights of `v1` and `v2`.
+pub(super) fn intersect(
+    ray: &Ray,
+    v0: Point3,
+    v1: Point3,
+    v2: Point3,
+    t_min: f64,
+    t_max: f64,
+) -> Option<(f64, f64, f64)> {
+    let edge1 = v1 - v0;
+    let edge2 = v2 - v0;
+    let p = ray.direction.cross(edge2);
+    let det = edge1.dot(p);
+    if det.abs() < 1e-12 {
+        // The ray is parallel to the triangle
+        return None;
+    }
+    let inv_det = 1.0 / det;
+
+    let s = ray.origin - v0;
+    let b1 = s.dot(p) * inv_det;
+    if !(0.0..=1.0).contains(&b1) {
+        return None;
+    }
+
+    let q = s.cross(edge1);
+    let b2 = ray.direction.dot(q) * inv_det;
+    if b2 < 0.0 || b1 + b2 > 1.0 {
+        return None;
+    }
+
+    let t = edge2.dot(q) * inv_det;
+    if t < t_min || t > t_max {
+        return None;
+    }
+    Some((t, b1, b2))
+}
+
+pub(super) fn bounding_box(v0: Point3, v1: Point3, v2: Point3) -> Aabb {
+    // Pad the box so that axis-aligned triangles still have some volume
+    let padding = Vec3::new(0.0001, 0.0001, 0.0001);
+    let min = Point3::new(
+        v0.x().min(v1.x()).min(v2.x()),
+        v0.y().min(v1.y()).min(v2.y()),
+        v0.z().min(v1.z()).min(v2.z()),
+    );
+    let max = Point3::new(
+        v0.x().max(v1.x()).max(v2.x()),
+        v0.y().max(v1.y()).max(v2.y()),
+        v0.z().max(v1.z()).max(v2.z()),
+    );
+    Aabb::new(min - padding, max + padding)
+}
+
+impl<M: Material> Hittable for Triangle<M> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
+        let (t, u, v) = intersect(ray, self.v0, self.v1, self.v2, t_min, t_max)?;
+        let outward_normal = (self.v1 - self.v0).cross(self.v2 - self.v0).unit();
+        Some(Hit::new_with_outward_normal(
+            ray,
+            t,
+            u,
+            v,
+            outward_normal,
+            &self.material,
+        ))
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
+        Some(bounding_box(self.v0, self.v1, self.v2))
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let hit = self.hit(&Ray::new(origin, direction, 0.0), 0.001, f64::INFINITY);
+        area_pdf(hit, direction, self.area())
+    }
+
+    fn random(&self, origin: Point3) -> Vec3 {
+        // Folds the points of the parallelogram beyond the diagonal back into the triangle
+        let mut rng = random::rng();
+        let (mut b1, mut b2) = (rng.gen::<f64>(), rng.gen::<f64>());
+        if b1 + b2 > 1.0 {
+            (b1, b2) = (1.0 - b1, 1.0 - b2);
+        }
+        self.v0 + b1 * (self.v1 - self.v0) + b2 * (self.v2 - self.v0) - origin
+    }
+
+    fn area(&self) -> f64 {
+        0.5 * (self.v1 - self.v0).cross(self.v2 - self.v0).length()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::materials::Lambertian;
+
+    use super::*;
+
+    fn triangle() -> Triangle<Lambertian<crate::textures::SolidColor>> {
+        Triangle::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Lambertian::new_with_color(Vec3::new(0.5, 0.5, 0.5)),
+        )
+    }
+
+    #[test]
+    fn test_hit_front() {
+        let triangle = triangle();
+        let ray = Ray::new(Point3::new(0.25, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+
+        let hit = triangle.hit(&ray, 0.0, f64::INFINITY).unwrap();
+        assert!((hit.t - 1.0).abs() < 1e-12);
+        assert!((hit.u - 0.25).abs() < 1e-12);
+        assert!((hit.v - 0.5).abs() < 1e-12);
+        assert!(hit.front_face);
+        assert_eq!(Vec3::new(0.0, 0.0, 1.0), hit.normal);
+    }
+
+    #[test]
+    fn test_hit_back() {
+        let triangle = triangle();
+        let ray = Ray::new(Point3::new(0.25, 0.25, -1.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+
+        let hit = triangle.hit(&ray, 0.0, f64::INFINITY).unwrap();
+        assert!(!hit.front_face);
+        assert_eq!(Vec3::new(0.0, 0.0, -1.0), hit.normal);
+    }
+
+    #[test]
+    fn test_miss() {
+        let triangle = triangle();
+        let outside = Ray::new(Point3::new(0.75, 0.75, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let parallel = Ray::new(Point3::new(-1.0, 0.25, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        let ray = Ray::new(Point3::new(0.25, 0.25, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+
+        assert!(triangle.hit(&outside, 0.0, f64::INFINITY).is_none());
+        assert!(triangle.hit(&parallel, 0.0, f64::INFINITY).is_none());
+        assert!(triangle.hit(&ray, 0.0, 0.5).is_none());
+    }
+}
@@ -0,0 +1,223 @@
+use std::sync::Arc;
+
+use crate::{
+    materials::Material,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{
+    triangle::{bounding_box, intersect},
+    Aabb, BvhTree, Hit, Hittable, HittableVec,
+};
+
+/// Indexed triangle mesh whose faces share their vertices.
+pub struct TriangleMesh<M: Material> {
+    faces: BvhTree,
+    data: Arc<MeshData<M>>,
+}
+
+struct MeshData<M: Material> {
+    positions: Vec<Point3>,
+    normals: Option<Vec<Vec3>>,
+    uvs: Option<Vec<(f64, f64)>>,
+    indices: Vec<[usize; 3]>,
+    material: M,
+}
+
+struct MeshFace<M: Material> {
+    data: Arc<MeshData<M>>,
+    index: usize,
+}
+
+impl<M: Material + 'static> TriangleMesh<M> {
+    pub fn new(positions: Vec<Point3>, indices: Vec<[usize; 3]>, material: M) -> TriangleMesh<M> {
+        TriangleMesh::new_with_attributes(positions, None, None, indices, material)
+    }
+
+    /// - `positions` - vertex positions
+    /// - `normals` - per-vertex normals, interpolated for shading if given
+    /// - `uvs` - per-vertex texture coordinates; barycentric coordinates are used if not given
+    /// - `indices` - vertex indices of each face, in counter-clockwise order seen from the front side
+    /// - `material` - material shared by all faces
+    pub fn new_with_attributes(
+        positions: Vec<Point3>,
+        normals: Option<Vec<Vec3>>,
+        uvs: Option<Vec<(f64, f64)>>,
+        indices: Vec<[usize; 3]>,
+        material: M,
+    ) -> TriangleMesh<M> {
+        assert!(!indices.is_empty());
+        assert!(indices.iter().flatten().all(|&i| i < positions.len()));
+        if let Some(normals) = &normals {
+            assert_eq!(positions.len(), normals.len());
+        }
+        if let Some(uvs) = &uvs {
+            assert_eq!(positions.len(), uvs.len());
+        }
+
+        let data = Arc::new(MeshData {
+            positions,
+            normals,
+            uvs,
+            indices,
+            material,
+        });
+        let faces: HittableVec = (0..data.indices.len())
+            .map(|index| {
+                Box::new(MeshFace {
+                    data: data.clone(),
+                    index,
+                }) as Box<dyn Hittable>
+            })
+            .collect();
+
+        TriangleMesh {
+            faces: BvhTree::new(faces, 0.0, 1.0),
+            data,
+        }
+    }
+
+    pub fn face_count(&self) -> usize {
+        self.data.indices.len()
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.data.positions.len()
+    }
+}
+
+impl<M: Material> MeshFace<M> {
+    fn vertices(&self) -> (Point3, Point3, Point3) {
+        let [i0, i1, i2] = self.data.indices[self.index];
+        let positions = &self.data.positions;
+        (positions[i0], positions[i1], positions[i2])
+    }
+}
+
+impl<M: Material> Hittable for MeshFace<M> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
+        let (v0, v1, v2) = self.vertices();
+        let (t, b1, b2) = intersect(ray, v0, v1, v2, t_min, t_max)?;
+        let b0 = 1.0 - b1 - b2;
+        let [i0, i1, i2] = self.data.indices[self.index];
+
+        let geometric_normal = (v1 - v0).cross(v2 - v0).unit();
+        let outward_normal = match &self.data.normals {
+            Some(normals) => {
+                let normal = (b0 * normals[i0] + b1 * normals[i1] + b2 * normals[i2]).unit();
+                // Keep the shading normal on the same side as the face
+                if normal.dot(geometric_normal) < 0.0 {
+                    -normal
+                } else {
+                    normal
+                }
+            }
+            None => geometric_normal,
+        };
+        let (u, v) = match &self.data.uvs {
+            Some(uvs) => (
+                b0 * uvs[i0].0 + b1 * uvs[i1].0 + b2 * uvs[i2].0,
+                b0 * uvs[i0].1 + b1 * uvs[i1].1 + b2 * uvs[i2].1,
+            ),
+            None => (b1, b2),
+        };
+
+        let mut hit =
+            Hit::new_with_outward_normal(ray, t, u, v, outward_normal, &self.data.material);
+        hit.vertices = Some(([i0, i1, i2], [b0, b1, b2]));
+        Some(hit)
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
+        let (v0, v1, v2) = self.vertices();
+        Some(bounding_box(v0, v1, v2))
+    }
+}
+
+impl<M: Material> Hittable for TriangleMesh<M> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
+        self.faces.hit(ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        self.faces.bounding_box(time0, time1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::materials::Lambertian;
+
+    use super::*;
+
+    fn quad(
+        normals: Option<Vec<Vec3>>,
+        uvs: Option<Vec<(f64, f64)>>,
+    ) -> TriangleMesh<Lambertian<crate::textures::SolidColor>> {
+        TriangleMesh::new_with_attributes(
+            vec![
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(1.0, 0.0, 0.0),
+                Point3::new(1.0, 1.0, 0.0),
+                Point3::new(0.0, 1.0, 0.0),
+            ],
+            normals,
+            uvs,
+            vec![[0, 1, 2], [0, 2, 3]],
+            Lambertian::new_with_color(Vec3::new(0.5, 0.5, 0.5)),
+        )
+    }
+
+    #[test]
+    fn test_hit_uses_barycentrics_without_uvs() {
+        let mesh = quad(None, None);
+        let ray = Ray::new(Point3::new(0.25, 0.75, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+
+        let hit = mesh.hit(&ray, 0.0, f64::INFINITY).unwrap();
+        assert!((hit.t - 1.0).abs() < 1e-12);
+        // second face: p = 0.25 * v0 + 0.25 * v2 + 0.5 * v3
+        assert!((hit.u - 0.25).abs() < 1e-12);
+        assert!((hit.v - 0.5).abs() < 1e-12);
+        assert_eq!(Vec3::new(0.0, 0.0, 1.0), hit.normal);
+        let Some(([0, 2, 3], [b0, b1, b2])) = hit.vertices else {
+            panic!("the hit should be on the second face");
+        };
+        assert!((b0 - 0.25).abs() < 1e-12 && (b1 - 0.25).abs() < 1e-12 && (b2 - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_hit_interpolates_uvs() {
+        let mesh = quad(
+            None,
+            Some(vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]),
+        );
+        let ray = Ray::new(Point3::new(0.25, 0.75, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+
+        let hit = mesh.hit(&ray, 0.0, f64::INFINITY).unwrap();
+        assert!((hit.u - 0.25).abs() < 1e-12);
+        assert!((hit.v - 0.75).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_hit_interpolates_normals() {
+        let n0 = Vec3::new(-1.0, 0.0, 1.0).unit();
+        let n1 = Vec3::new(1.0, 0.0, 1.0).unit();
+        let mesh = quad(Some(vec![n0, n1, n1, n0]), None);
+        let ray = Ray::new(Point3::new(0.5, 0.25, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+
+        let hit = mesh.hit(&ray, 0.0, f64::INFINITY).unwrap();
+        assert!((hit.normal - Vec3::new(0.0, 0.0, 1.0)).is_near_zero());
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let mesh = quad(None, None);
+        let bbox = mesh.bounding_box(0.0, 1.0).unwrap();
+
+        assert!(bbox.min.x() <= 0.0 && bbox.min.y() <= 0.0 && bbox.min.z() < 0.0);
+        assert!(bbox.max.x() >= 1.0 && bbox.max.y() >= 1.0 && bbox.max.z() > 0.0);
+        assert_eq!(2, mesh.face_count());
+        assert_eq!(4, mesh.vertex_count());
+    }
+}
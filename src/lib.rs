@@ -0,0 +1,28 @@
+//! A path tracer following the "Ray Tracing in One Weekend" series.
+//!
+//! Build a [`Scene`], either in code or from a TOML file with [`Scene::new_with_filename`],
+//! and pass its world and camera to [`render`].
+
+pub mod camera;
+pub mod hittables;
+pub mod lights;
+pub mod loaders;
+pub mod materials;
+mod math;
+pub mod matrix;
+pub mod output;
+mod polynomial;
+pub mod quaternion;
+pub mod random;
+pub mod ray;
+mod render;
+pub mod scene;
+pub mod scenes;
+pub mod sdf;
+pub mod textures;
+pub mod tone_mapping;
+pub mod vec3;
+
+pub use camera::Camera;
+pub use render::{ray_color, ray_color_mis, ray_color_next_event, render, render_with_progress};
+pub use scene::{CameraSettings, Integrator, RenderSettings, Scene};
@@ -0,0 +1,16 @@
+//! Lights that integrators sample directly, gathered by scenes in a [`LightList`].
+
+mod alias_table;
+mod area_light;
+mod directional_light;
+mod light;
+mod light_list;
+mod point_light;
+mod spot_light;
+
+pub use area_light::AreaLight;
+pub use directional_light::DirectionalLight;
+pub use light::{Light, LightSample};
+pub use light_list::LightList;
+pub use point_light::PointLight;
+pub use spot_light::SpotLight;
@@ -0,0 +1,99 @@
+use rand::Rng;
+
+use crate::random;
+
+/// Picks indices in proportion to their weights in constant time (Vose's alias method).
+///
+/// Each index has a column of height one, split between the index itself up to its threshold and
+/// an alias above it.
+#[derive(Debug, Clone, Default)]
+pub struct AliasTable {
+    probabilities: Vec<f64>,
+    thresholds: Vec<f64>,
+    aliases: Vec<usize>,
+}
+
+impl AliasTable {
+    /// Weights must not be negative; if they are all zero, every index is equally likely.
+    pub fn new(weights: &[f64]) -> AliasTable {
+        let n = weights.len();
+        let total: f64 = weights.iter().sum();
+        let probabilities = if total > 0.0 {
+            weights.iter().map(|w| w / total).collect::<Vec<_>>()
+        } else {
+            vec![1.0 / n as f64; n]
+        };
+
+        let mut scaled = probabilities
+            .iter()
+            .map(|p| p * n as f64)
+            .collect::<Vec<_>>();
+        let (mut small, mut large): (Vec<usize>, Vec<usize>) =
+            (0..n).partition(|&i| scaled[i] < 1.0);
+        let mut thresholds = vec![1.0; n];
+        let mut aliases = (0..n).collect::<Vec<_>>();
+        while let (Some(&s), Some(&l)) = (small.last(), large.last()) {
+            small.pop();
+            large.pop();
+            // The column of `s` is filled up with `l`
+            thresholds[s] = scaled[s];
+            aliases[s] = l;
+            scaled[l] -= 1.0 - scaled[s];
+            if scaled[l] < 1.0 {
+                small.push(l);
+            } else {
+                large.push(l);
+            }
+        }
+        // Whatever is left is full up to rounding errors
+
+        AliasTable {
+            probabilities,
+            thresholds,
+            aliases,
+        }
+    }
+
+    /// Probability with which `sample` returns `index`.
+    pub fn probability(&self, index: usize) -> f64 {
+        self.probabilities[index]
+    }
+
+    /// Panics if the table is empty.
+    pub fn sample(&self) -> usize {
+        let mut rng = random::rng();
+        let index = rng.gen_range(0..self.probabilities.len());
+        if rng.gen::<f64>() < self.thresholds[index] {
+            index
+        } else {
+            self.aliases[index]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample() {
+        let weights = [1.0, 0.0, 3.0, 0.5, 2.5, 1.0];
+        let table = AliasTable::new(&weights);
+        random::seed(1);
+
+        let n = 400_000;
+        let mut counts = [0; 6];
+        for _ in 0..n {
+            counts[table.sample()] += 1;
+        }
+        for (i, count) in counts.into_iter().enumerate() {
+            let expected = weights[i] / 8.0;
+            assert_eq!(expected, table.probability(i));
+            assert!((count as f64 / n as f64 - expected).abs() < 0.005);
+        }
+        assert_eq!(0, counts[1]);
+
+        let uniform = AliasTable::new(&[0.0, 0.0]);
+        assert_eq!(0.5, uniform.probability(1));
+    }
+}
@@ -0,0 +1,46 @@
+use std::f64::consts::PI;
+
+use crate::{
+    hittables::Hittable,
+    tone_mapping::luminance,
+    vec3::{Color, Point3, Vec3},
+};
+
+use super::{Light, LightSample};
+
+/// Emissive object sampled through its `Hittable` implementation, such as a rectangle, a quad, a
+/// triangle or a sphere.
+///
+/// The object is usually also part of the world, where its material gives the light it emits.
+#[derive(Debug, Clone)]
+pub struct AreaLight<H: Hittable> {
+    shape: H,
+    power: f64,
+}
+
+impl<H: Hittable> AreaLight<H> {
+    /// `radiance` is the light emitted by the surface, on average if it varies, and only sets the
+    /// power of the light.
+    pub fn new(shape: H, radiance: Color) -> AreaLight<H> {
+        let power = PI * shape.area() * luminance(radiance);
+        AreaLight { shape, power }
+    }
+}
+
+impl<H: Hittable> Light for AreaLight<H> {
+    fn power(&self) -> f64 {
+        self.power
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        self.shape.pdf_value(origin, direction)
+    }
+
+    fn sample(&self, origin: Point3) -> LightSample {
+        let direction = self.shape.random(origin);
+        LightSample::Area {
+            direction,
+            pdf: self.shape.pdf_value(origin, direction),
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use std::f64::consts::PI;
+
+use crate::{
+    tone_mapping::luminance,
+    vec3::{Color, Point3, Vec3},
+};
+
+use super::{Light, LightSample};
+
+/// Light arriving along a single direction from infinitely far away, like sunlight.
+#[derive(Debug, Clone, Copy)]
+pub struct DirectionalLight {
+    /// Toward the light, against the way it travels
+    toward: Vec3,
+    irradiance: Color,
+    power: f64,
+}
+
+impl DirectionalLight {
+    /// `direction` is the way the light travels, and `irradiance` the light arriving on a surface
+    /// facing it.
+    ///
+    /// `radius` is that of a sphere around the lit part of the scene, and only sets the power of
+    /// the light. Panics if `direction` is zero.
+    pub fn new(direction: Vec3, irradiance: Color, radius: f64) -> DirectionalLight {
+        assert!(!direction.is_near_zero(), "the direction must not be zero");
+        DirectionalLight {
+            toward: -direction.unit(),
+            irradiance,
+            power: PI * radius * radius * luminance(irradiance),
+        }
+    }
+}
+
+impl Light for DirectionalLight {
+    fn power(&self) -> f64 {
+        self.power
+    }
+
+    fn pdf_value(&self, _origin: Point3, _direction: Vec3) -> f64 {
+        0.0
+    }
+
+    fn sample(&self, _origin: Point3) -> LightSample {
+        LightSample::Delta {
+            direction: self.toward,
+            distance: f64::INFINITY,
+            irradiance: self.irradiance,
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use crate::vec3::{Color, Point3, Vec3};
+
+/// Source of light whose directions can be sampled, besides being found by random bounces.
+pub trait Light: Send + Sync {
+    /// Power emitted by the light, only compared to that of the other lights of a scene.
+    fn power(&self) -> f64;
+
+    /// Density over solid angle with which `sample` picks `direction` from `origin`, zero for
+    /// lights at a single point or in a single direction.
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64;
+
+    fn sample(&self, origin: Point3) -> LightSample;
+}
+
+impl<L: Light + ?Sized> Light for Box<L> {
+    fn power(&self) -> f64 {
+        (**self).power()
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        (**self).pdf_value(origin, direction)
+    }
+
+    fn sample(&self, origin: Point3) -> LightSample {
+        (**self).sample(origin)
+    }
+}
+
+/// Direction toward a light picked by `Light::sample`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LightSample {
+    /// Toward a light with an area, whose light is found by tracing a ray along `direction`
+    Area {
+        direction: Vec3,
+        /// Density over solid angle of `direction`, over all the lights of a `LightList`
+        pdf: f64,
+    },
+    /// From a light at a single point or in a single direction, which no random bounce can reach
+    Delta {
+        direction: Vec3,
+        /// Distance to the light along `direction`, which is a unit vector
+        distance: f64,
+        /// Light arriving on a surface facing the light, unless something lies in between; divided
+        /// by the probability of picking the light in a `LightList`
+        irradiance: Color,
+    },
+}
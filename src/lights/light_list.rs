@@ -0,0 +1,167 @@
+use std::sync::OnceLock;
+
+use crate::vec3::{Point3, Vec3};
+
+use super::{alias_table::AliasTable, Light, LightSample};
+
+/// The lights of a scene, picked in proportion to their power when sampled.
+#[derive(Default)]
+pub struct LightList {
+    lights: Vec<Box<dyn Light>>,
+    /// Built from the powers of the lights on first use, once they have all been pushed
+    table: OnceLock<AliasTable>,
+}
+
+impl LightList {
+    pub fn new() -> LightList {
+        LightList::default()
+    }
+
+    pub fn push(&mut self, light: impl Light + 'static) {
+        self.lights.push(Box::new(light));
+        self.table.take();
+    }
+
+    fn table(&self) -> &AliasTable {
+        self.table.get_or_init(|| {
+            let powers = self
+                .lights
+                .iter()
+                .map(|light| light.power().max(0.0))
+                .collect::<Vec<_>>();
+            AliasTable::new(&powers)
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.lights.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lights.is_empty()
+    }
+
+    /// Density over solid angle with which `sample` picks `direction` from `origin`, which sums
+    /// over all the lights that the direction may have come from.
+    ///
+    /// Every light is hit-tested, so the cost grows with the number of lights; integrators only
+    /// ask for it when a bounce has reached an emissive surface.
+    pub fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let table = self.table();
+        self.lights
+            .iter()
+            .enumerate()
+            .map(|(i, light)| table.probability(i) * light.pdf_value(origin, direction))
+            .sum()
+    }
+
+    /// Picks a light in proportion to its power and a direction from `origin` toward it, or `None`
+    /// if there are no lights or the light cannot be reached.
+    pub fn sample(&self, origin: Point3) -> Option<LightSample> {
+        if self.is_empty() {
+            return None;
+        }
+        let index = self.table().sample();
+        match self.lights[index].sample(origin) {
+            LightSample::Area { direction, .. } => {
+                let pdf = self.pdf_value(origin, direction);
+                (pdf > 0.0).then_some(LightSample::Area { direction, pdf })
+            }
+            LightSample::Delta {
+                direction,
+                distance,
+                irradiance,
+            } => Some(LightSample::Delta {
+                direction,
+                distance,
+                irradiance: irradiance / self.table().probability(index),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hittables::XzRect,
+        lights::{AreaLight, PointLight},
+        materials::DiffuseLight,
+        random,
+        textures::SolidColor,
+        vec3::Color,
+    };
+
+    use super::*;
+
+    fn light(x: f64, radiance: f64) -> AreaLight<XzRect<DiffuseLight<SolidColor>>> {
+        let color = Color::new(radiance, radiance, radiance);
+        AreaLight::new(
+            XzRect::new(
+                x - 0.5,
+                x + 0.5,
+                -0.5,
+                0.5,
+                2.0,
+                DiffuseLight::new_with_color(color),
+            ),
+            color,
+        )
+    }
+
+    #[test]
+    fn test_sample_in_proportion_to_power() {
+        let mut lights = LightList::new();
+        assert!(lights.sample(Point3::default()).is_none());
+        lights.push(light(-2.0, 1.0));
+        assert_eq!(1.0, lights.table().probability(0));
+        // The table is built again with the new light
+        lights.push(light(2.0, 3.0));
+        random::seed(1);
+
+        let n = 100_000;
+        let origin = Point3::default();
+        let right = (0..n)
+            .filter(|_| {
+                let Some(LightSample::Area { direction, .. }) = lights.sample(origin) else {
+                    panic!("both lights have an area");
+                };
+                direction.x() > 0.0
+            })
+            .count();
+        assert!((right as f64 / n as f64 - 0.75).abs() < 0.01);
+
+        // Straight up misses both lights, and each light is only sampled a quarter or three
+        // quarters of the time
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        assert_eq!(0.0, lights.pdf_value(origin, up));
+        let toward_right = Vec3::new(2.0, 2.0, 0.0);
+        let pdf = light(2.0, 3.0).pdf_value(origin, toward_right);
+        assert!((lights.pdf_value(origin, toward_right) - 0.75 * pdf).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_delta_light() {
+        let mut lights = LightList::new();
+        lights.push(PointLight::new(
+            Point3::new(0.0, 2.0, 0.0),
+            Color::new(4.0, 4.0, 4.0),
+        ));
+        lights.push(light(0.0, 1.0));
+        let probability = lights.table().probability(0);
+        random::seed(1);
+
+        // Picking the point light only some of the time is made up for by its irradiance
+        let origin = Point3::default();
+        let sample = (0..100)
+            .find_map(|_| match lights.sample(origin) {
+                Some(LightSample::Delta { irradiance, .. }) => Some(irradiance),
+                _ => None,
+            })
+            .unwrap();
+        assert!((sample - Color::new(1.0, 1.0, 1.0) / probability).length() < 1e-12);
+        // and it adds nothing to the density of the directions toward the area light
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let pdf = light(0.0, 1.0).pdf_value(origin, up);
+        assert!((lights.pdf_value(origin, up) - (1.0 - probability) * pdf).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,68 @@
+use std::f64::consts::PI;
+
+use crate::{
+    tone_mapping::luminance,
+    vec3::{Color, Point3, Vec3},
+};
+
+use super::{Light, LightSample};
+
+/// Light emitted equally in all directions from a single point.
+#[derive(Debug, Clone, Copy)]
+pub struct PointLight {
+    position: Point3,
+    intensity: Color,
+}
+
+impl PointLight {
+    /// `intensity` is the light emitted per unit solid angle, which falls off with the square of
+    /// the distance.
+    pub fn new(position: Point3, intensity: Color) -> PointLight {
+        PointLight {
+            position,
+            intensity,
+        }
+    }
+}
+
+impl Light for PointLight {
+    fn power(&self) -> f64 {
+        4.0 * PI * luminance(self.intensity)
+    }
+
+    fn pdf_value(&self, _origin: Point3, _direction: Vec3) -> f64 {
+        0.0
+    }
+
+    fn sample(&self, origin: Point3) -> LightSample {
+        let offset = self.position - origin;
+        let distance = offset.length();
+        LightSample::Delta {
+            direction: offset / distance,
+            distance,
+            irradiance: self.intensity / (distance * distance),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inverse_square() {
+        let light = PointLight::new(Point3::new(0.0, 2.0, 0.0), Color::new(8.0, 4.0, 2.0));
+
+        let LightSample::Delta {
+            direction,
+            distance,
+            irradiance,
+        } = light.sample(Point3::new(0.0, 0.0, 0.0))
+        else {
+            panic!("a point light is a delta light");
+        };
+        assert_eq!(Vec3::new(0.0, 1.0, 0.0), direction);
+        assert_eq!(2.0, distance);
+        assert_eq!(Color::new(2.0, 1.0, 0.5), irradiance);
+    }
+}
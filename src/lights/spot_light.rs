@@ -0,0 +1,113 @@
+use std::f64::consts::PI;
+
+use crate::{
+    tone_mapping::luminance,
+    vec3::{Color, Point3, Vec3},
+};
+
+use super::{Light, LightSample};
+
+/// Point light shining in a cone, at full intensity within `inner_angle` of its axis and fading
+/// smoothly to nothing at `outer_angle`.
+#[derive(Debug, Clone, Copy)]
+pub struct SpotLight {
+    position: Point3,
+    axis: Vec3,
+    intensity: Color,
+    cos_inner: f64,
+    cos_outer: f64,
+}
+
+impl SpotLight {
+    /// The angles are in radians from the axis along `direction`, and `intensity` is the light
+    /// emitted per unit solid angle within the inner cone.
+    ///
+    /// Panics if `direction` is zero or the angles are not `0 <= inner_angle <= outer_angle`.
+    pub fn new(
+        position: Point3,
+        direction: Vec3,
+        intensity: Color,
+        inner_angle: f64,
+        outer_angle: f64,
+    ) -> SpotLight {
+        assert!(!direction.is_near_zero(), "the direction must not be zero");
+        assert!(
+            0.0 <= inner_angle && inner_angle <= outer_angle,
+            "the angles must be 0 <= inner_angle <= outer_angle"
+        );
+        SpotLight {
+            position,
+            axis: direction.unit(),
+            intensity,
+            cos_inner: inner_angle.cos(),
+            cos_outer: outer_angle.cos(),
+        }
+    }
+
+    /// Fraction of the intensity emitted at an angle with the cosine `cos_theta` from the axis.
+    fn falloff(&self, cos_theta: f64) -> f64 {
+        if cos_theta >= self.cos_inner {
+            1.0
+        } else if cos_theta <= self.cos_outer {
+            0.0
+        } else {
+            let x = (cos_theta - self.cos_outer) / (self.cos_inner - self.cos_outer);
+            x * x * (3.0 - 2.0 * x)
+        }
+    }
+}
+
+impl Light for SpotLight {
+    /// Approximates the falloff as linear in the cosine, halfway between the cones.
+    fn power(&self) -> f64 {
+        2.0 * PI * (1.0 - 0.5 * (self.cos_inner + self.cos_outer)) * luminance(self.intensity)
+    }
+
+    fn pdf_value(&self, _origin: Point3, _direction: Vec3) -> f64 {
+        0.0
+    }
+
+    fn sample(&self, origin: Point3) -> LightSample {
+        let offset = self.position - origin;
+        let distance = offset.length();
+        let direction = offset / distance;
+        let falloff = self.falloff(-direction.dot(self.axis));
+        LightSample::Delta {
+            direction,
+            distance,
+            irradiance: self.intensity * falloff / (distance * distance),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_falloff() {
+        let light = SpotLight::new(
+            Point3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, -2.0, 0.0),
+            Color::new(1.0, 1.0, 1.0),
+            PI / 6.0,
+            PI / 4.0,
+        );
+        let irradiance = |x: f64| {
+            let LightSample::Delta { irradiance, .. } = light.sample(Point3::new(x, 0.0, 0.0))
+            else {
+                panic!("a spot light is a delta light");
+            };
+            irradiance.x()
+        };
+
+        assert_eq!(1.0, irradiance(0.0));
+        // Within the inner cone at 30°, only falling off with the distance
+        assert!((irradiance(0.5) - 0.8).abs() < 1e-12);
+        // Fading between the cones, and dark beyond 45°
+        let (a, b) = (irradiance(0.65), irradiance(0.8));
+        assert!(0.0 < b && b < a && a < 1.0 / (1.0 + 0.65 * 0.65));
+        assert_eq!(0.0, irradiance(1.01));
+        assert_eq!(0.0, irradiance(-3.0));
+    }
+}
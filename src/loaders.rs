@@ -0,0 +1,7 @@
+mod obj;
+mod ply;
+mod stl;
+
+pub use obj::load_obj;
+pub use ply::{load_ply, load_ply_with_vertex_colors};
+pub use stl::load_stl;
@@ -0,0 +1,443 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    str::SplitWhitespace,
+    sync::Arc,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use crate::{
+    hittables::{HittableVec, TriangleMesh},
+    materials::{Dielectric, DiffuseLight, Lambertian, Material},
+    textures::ImageTexture,
+    vec3::{Color, Point3, Vec3},
+};
+
+/// Loads a Wavefront OBJ file and the MTL libraries it refers to.
+///
+/// Faces are grouped by material and each group becomes one `TriangleMesh`.
+pub fn load_obj(filename: impl AsRef<Path>) -> Result<HittableVec> {
+    let filename = filename.as_ref();
+    let source = fs::read_to_string(filename)
+        .with_context(|| format!("failed to read {}", filename.display()))?;
+    let base_dir = filename.parent().unwrap_or_else(|| Path::new(""));
+    parse_obj(&source, base_dir).with_context(|| format!("failed to load {}", filename.display()))
+}
+
+/// Material description read from an MTL file.
+#[derive(Debug, Clone, PartialEq)]
+struct MtlMaterial {
+    diffuse: Color,
+    emission: Color,
+    refraction_index: Option<f64>,
+    dissolve: f64,
+    diffuse_map: Option<PathBuf>,
+}
+
+impl Default for MtlMaterial {
+    fn default() -> MtlMaterial {
+        MtlMaterial {
+            diffuse: Color::new(0.8, 0.8, 0.8),
+            emission: Color::default(),
+            refraction_index: None,
+            dissolve: 1.0,
+            diffuse_map: None,
+        }
+    }
+}
+
+impl MtlMaterial {
+    /// An emissive material becomes a light, and a material that is transparent or has an index
+    /// of refraction becomes glass.
+    fn to_material(&self) -> Result<Arc<dyn Material>> {
+        if self.emission.x() > 0.0 || self.emission.y() > 0.0 || self.emission.z() > 0.0 {
+            return Ok(Arc::new(DiffuseLight::new_with_color(self.emission)));
+        }
+        if self.dissolve < 1.0 || self.refraction_index.is_some() {
+            return Ok(Arc::new(Dielectric::new(
+                self.refraction_index.unwrap_or(1.5),
+            )));
+        }
+        if let Some(diffuse_map) = &self.diffuse_map {
+            let texture = ImageTexture::new_with_filename(diffuse_map)
+                .with_context(|| format!("failed to load {}", diffuse_map.display()))?;
+            return Ok(Arc::new(Lambertian::new(texture)));
+        }
+        Ok(Arc::new(Lambertian::new_with_color(self.diffuse)))
+    }
+}
+
+/// Faces sharing one material, with their own compacted vertex list.
+#[derive(Default)]
+struct Group {
+    positions: Vec<Point3>,
+    normals: Vec<Option<Vec3>>,
+    uvs: Vec<Option<(f64, f64)>>,
+    indices: Vec<[usize; 3]>,
+    vertices: HashMap<(usize, Option<usize>, Option<usize>), usize>,
+}
+
+impl Group {
+    fn into_mesh(self, material: Arc<dyn Material>) -> TriangleMesh<Arc<dyn Material>> {
+        // Vertex attributes are only usable if every vertex of the group has them
+        let normals = self.normals.into_iter().collect::<Option<Vec<_>>>();
+        let uvs = self.uvs.into_iter().collect::<Option<Vec<_>>>();
+        TriangleMesh::new_with_attributes(self.positions, normals, uvs, self.indices, material)
+    }
+}
+
+fn parse_obj(source: &str, base_dir: &Path) -> Result<HittableVec> {
+    let mut positions = Vec::<Point3>::new();
+    let mut normals = Vec::<Vec3>::new();
+    let mut uvs = Vec::<(f64, f64)>::new();
+    let mut materials = HashMap::<String, MtlMaterial>::new();
+    let mut groups = Vec::<(Option<String>, Group)>::new();
+    // Index into `groups` of each material name
+    let mut group_indices = HashMap::<Option<String>, usize>::new();
+    let mut current = None::<String>;
+
+    for (line_number, line) in source.lines().enumerate() {
+        let line_number = line_number + 1;
+        let mut tokens = line.split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+        match keyword {
+            "v" => {
+                positions.push(parse_vec3(&mut tokens).with_context(|| line_error(line_number))?)
+            }
+            "vn" => normals.push(parse_vec3(&mut tokens).with_context(|| line_error(line_number))?),
+            "vt" => {
+                let u = parse_f64(tokens.next()).with_context(|| line_error(line_number))?;
+                // The second coordinate is optional and defaults to 0
+                let v = match tokens.next() {
+                    Some(token) => {
+                        parse_f64(Some(token)).with_context(|| line_error(line_number))?
+                    }
+                    None => 0.0,
+                };
+                uvs.push((u, v));
+            }
+            "f" => {
+                let corners = tokens
+                    .map(|token| parse_corner(token, positions.len(), uvs.len(), normals.len()))
+                    .collect::<Result<Vec<_>>>()
+                    .with_context(|| line_error(line_number))?;
+                if corners.len() < 3 {
+                    bail!(
+                        "{}: a face needs at least 3 vertices",
+                        line_error(line_number)
+                    );
+                }
+
+                let index = match group_indices.get(&current) {
+                    Some(&index) => index,
+                    None => {
+                        groups.push((current.clone(), Group::default()));
+                        group_indices.insert(current.clone(), groups.len() - 1);
+                        groups.len() - 1
+                    }
+                };
+                let group = &mut groups[index].1;
+                let indices = corners
+                    .into_iter()
+                    .map(|key| {
+                        *group.vertices.entry(key).or_insert_with(|| {
+                            let (p, t, n) = key;
+                            group.positions.push(positions[p]);
+                            group.uvs.push(t.map(|t| uvs[t]));
+                            group.normals.push(n.map(|n| normals[n]));
+                            group.positions.len() - 1
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                // Triangulate polygons as a fan around the first vertex
+                for i in 1..indices.len() - 1 {
+                    group.indices.push([indices[0], indices[i], indices[i + 1]]);
+                }
+            }
+            "mtllib" => {
+                let names = tokens.collect::<Vec<_>>();
+                if names.is_empty() {
+                    bail!("{}: missing file name", line_error(line_number));
+                }
+                for name in names {
+                    let filename = base_dir.join(name);
+                    let source = fs::read_to_string(&filename).with_context(|| {
+                        format!(
+                            "{}: failed to read {}",
+                            line_error(line_number),
+                            filename.display()
+                        )
+                    })?;
+                    let mtl_dir = filename.parent().unwrap_or(base_dir);
+                    parse_mtl(&source, mtl_dir, &mut materials)
+                        .with_context(|| format!("failed to load {}", filename.display()))?;
+                }
+            }
+            "usemtl" => {
+                let Some(name) = tokens.next() else {
+                    bail!("{}: missing material name", line_error(line_number));
+                };
+                if !materials.contains_key(name) {
+                    bail!("{}: unknown material `{name}`", line_error(line_number));
+                }
+                current = Some(name.to_string());
+            }
+            // Grouping, smoothing and other statements do not affect rendering
+            _ => {}
+        }
+    }
+
+    if groups.is_empty() {
+        bail!("no faces found");
+    }
+
+    let mut world = HittableVec::new();
+    for (name, group) in groups {
+        let material = match name {
+            Some(name) => materials[&name].to_material()?,
+            None => MtlMaterial::default().to_material()?,
+        };
+        world.push(Box::new(group.into_mesh(material)));
+    }
+    Ok(world)
+}
+
+fn parse_mtl(
+    source: &str,
+    base_dir: &Path,
+    materials: &mut HashMap<String, MtlMaterial>,
+) -> Result<()> {
+    let mut current = None::<String>;
+
+    for (line_number, line) in source.lines().enumerate() {
+        let line_number = line_number + 1;
+        let mut tokens = line.split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+        if keyword.starts_with('#') {
+            continue;
+        }
+        if keyword == "newmtl" {
+            let Some(name) = tokens.next() else {
+                bail!("{}: missing material name", line_error(line_number));
+            };
+            materials.insert(name.to_string(), MtlMaterial::default());
+            current = Some(name.to_string());
+            continue;
+        }
+
+        let material = match &current {
+            Some(name) => materials.get_mut(name).unwrap(),
+            None => bail!(
+                "{}: `{keyword}` appears before `newmtl`",
+                line_error(line_number)
+            ),
+        };
+        match keyword {
+            "Kd" => {
+                material.diffuse =
+                    parse_vec3(&mut tokens).with_context(|| line_error(line_number))?
+            }
+            "Ke" => {
+                material.emission =
+                    parse_vec3(&mut tokens).with_context(|| line_error(line_number))?
+            }
+            "Ni" => {
+                material.refraction_index =
+                    Some(parse_f64(tokens.next()).with_context(|| line_error(line_number))?)
+            }
+            "d" => {
+                material.dissolve =
+                    parse_f64(tokens.next()).with_context(|| line_error(line_number))?
+            }
+            "Tr" => {
+                material.dissolve =
+                    1.0 - parse_f64(tokens.next()).with_context(|| line_error(line_number))?
+            }
+            "map_Kd" => {
+                // Options such as `-s` may precede the file name, which comes last
+                let Some(name) = tokens.last() else {
+                    bail!("{}: missing file name", line_error(line_number));
+                };
+                material.diffuse_map = Some(base_dir.join(name));
+            }
+            // Specular and other parameters have no counterpart in our materials
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn line_error(line_number: usize) -> String {
+    format!("line {line_number}")
+}
+
+fn parse_f64(token: Option<&str>) -> Result<f64> {
+    let Some(token) = token else {
+        bail!("missing number");
+    };
+    token
+        .parse::<f64>()
+        .map_err(|_| anyhow!("invalid number `{token}`"))
+}
+
+fn parse_vec3(tokens: &mut SplitWhitespace) -> Result<Vec3> {
+    Ok(Vec3::new(
+        parse_f64(tokens.next())?,
+        parse_f64(tokens.next())?,
+        parse_f64(tokens.next())?,
+    ))
+}
+
+/// Parses `v`, `v/vt`, `v//vn` or `v/vt/vn` into zero-based indices.
+fn parse_corner(
+    token: &str,
+    position_count: usize,
+    uv_count: usize,
+    normal_count: usize,
+) -> Result<(usize, Option<usize>, Option<usize>)> {
+    let mut parts = token.split('/');
+    let position = parse_index(parts.next(), position_count)?
+        .ok_or_else(|| anyhow!("missing vertex index in `{token}`"))?;
+    let uv = parse_index(parts.next(), uv_count)?;
+    let normal = parse_index(parts.next(), normal_count)?;
+    if parts.next().is_some() {
+        bail!("invalid face vertex `{token}`");
+    }
+    Ok((position, uv, normal))
+}
+
+/// Resolves a one-based or negative (relative) OBJ index.
+fn parse_index(token: Option<&str>, count: usize) -> Result<Option<usize>> {
+    let Some(token) = token.filter(|token| !token.is_empty()) else {
+        return Ok(None);
+    };
+    let index = token
+        .parse::<isize>()
+        .map_err(|_| anyhow!("invalid index `{token}`"))?;
+    let resolved = if index > 0 {
+        index - 1
+    } else {
+        count as isize + index
+    };
+    if index == 0 || resolved < 0 || resolved >= count as isize {
+        bail!("index {index} is out of range");
+    }
+    Ok(Some(resolved as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hittables::{Hittable, Sphere},
+        ray::Ray,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_parse_obj_triangulates_polygons() {
+        let source = "
+# unit square
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+f 1/1 2/2 3/3 -1/-1
+";
+        let world = parse_obj(source, Path::new("")).unwrap();
+        assert_eq!(1, world.len());
+
+        let ray = Ray::new(Point3::new(0.25, 0.75, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let hit = world.hit(&ray, 0.0, f64::INFINITY).unwrap();
+        assert!((hit.u - 0.25).abs() < 1e-12);
+        assert!((hit.v - 0.75).abs() < 1e-12);
+    }
+
+    fn parse_obj_error(source: &str) -> String {
+        match parse_obj(source, Path::new("")) {
+            Ok(_) => panic!("parse_obj should fail"),
+            Err(error) => format!("{error:#}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_obj_reports_line_numbers() {
+        assert_eq!(
+            "line 3: invalid number `x`",
+            parse_obj_error("v 0 0 0\nv 1 0 0\nv 0 1 x\n")
+        );
+        assert_eq!(
+            "line 4: index 4 is out of range",
+            parse_obj_error("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n")
+        );
+        assert_eq!(
+            "line 2: unknown material `missing`",
+            parse_obj_error("v 0 0 0\nusemtl missing\n")
+        );
+    }
+
+    #[test]
+    fn test_parse_mtl() {
+        let source = "
+newmtl white
+Kd 0.73 0.73 0.73
+newmtl light
+Ke 15 15 15
+newmtl glass
+Ni 1.45
+d 0.1
+newmtl earth
+map_Kd -s 1 1 1 textures/earthmap.jpg
+";
+        let mut materials = HashMap::new();
+        parse_mtl(source, Path::new("assets"), &mut materials).unwrap();
+
+        assert_eq!(Color::new(0.73, 0.73, 0.73), materials["white"].diffuse);
+        assert_eq!(Color::new(15.0, 15.0, 15.0), materials["light"].emission);
+        assert_eq!(Some(1.45), materials["glass"].refraction_index);
+        assert_eq!(0.1, materials["glass"].dissolve);
+        assert_eq!(
+            Some(Path::new("assets/textures/earthmap.jpg").to_path_buf()),
+            materials["earth"].diffuse_map
+        );
+    }
+
+    #[test]
+    fn test_mtl_material() {
+        let ray = Ray::new(Point3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+        // Glass scatters specularly, without a density to sample the direction with
+        let is_specular = |material: MtlMaterial| {
+            let sphere = Sphere::new(Point3::default(), 1.0, material.to_material().unwrap());
+            let hit = sphere.hit(&ray, 0.001, f64::INFINITY).unwrap();
+            hit.material.scatter(&ray, &hit).unwrap().pdf.is_none()
+        };
+
+        assert!(!is_specular(MtlMaterial::default()));
+        assert!(is_specular(MtlMaterial {
+            dissolve: 0.5,
+            ..MtlMaterial::default()
+        }));
+        assert!(is_specular(MtlMaterial {
+            refraction_index: Some(1.45),
+            ..MtlMaterial::default()
+        }));
+    }
+
+    #[test]
+    fn test_parse_mtl_requires_newmtl() {
+        let mut materials = HashMap::new();
+        let error = parse_mtl("\nKd 1 1 1\n", Path::new(""), &mut materials).unwrap_err();
+        assert_eq!("line 2: `Kd` appears before `newmtl`", format!("{error:#}"));
+    }
+}
@@ -0,0 +1,584 @@
+use std::{fs, path::Path, str::SplitAsciiWhitespace};
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use crate::{
+    hittables::TriangleMesh,
+    materials::{Lambertian, Material},
+    textures::VertexColorTexture,
+    vec3::{Color, Point3, Vec3},
+};
+
+/// Loads an ASCII or binary PLY file as a mesh with a single material.
+///
+/// Vertex normals and texture coordinates are used if the file has them.
+pub fn load_ply<M: Material + 'static>(
+    filename: impl AsRef<Path>,
+    material: M,
+) -> Result<TriangleMesh<M>> {
+    let ply = read_ply(filename.as_ref())?;
+    Ok(TriangleMesh::new_with_attributes(
+        ply.positions,
+        ply.normals,
+        ply.uvs,
+        ply.indices,
+        material,
+    ))
+}
+
+/// Loads an ASCII or binary PLY file whose vertices have `red`, `green` and `blue` properties.
+///
+/// The mesh gets a single `Lambertian` material interpolating the colors of the vertices.
+pub fn load_ply_with_vertex_colors(
+    filename: impl AsRef<Path>,
+) -> Result<TriangleMesh<Lambertian<VertexColorTexture>>> {
+    let filename = filename.as_ref();
+    let ply = read_ply(filename)?;
+    let Some(colors) = ply.colors else {
+        bail!("{} has no vertex colors", filename.display());
+    };
+    Ok(TriangleMesh::new_with_attributes(
+        ply.positions,
+        ply.normals,
+        ply.uvs,
+        ply.indices,
+        Lambertian::new(VertexColorTexture::new(colors)),
+    ))
+}
+
+fn read_ply(filename: &Path) -> Result<Ply> {
+    let bytes =
+        fs::read(filename).with_context(|| format!("failed to read {}", filename.display()))?;
+    parse_ply(&bytes).with_context(|| format!("failed to load {}", filename.display()))
+}
+
+#[derive(Debug, Default)]
+struct Ply {
+    positions: Vec<Point3>,
+    normals: Option<Vec<Vec3>>,
+    uvs: Option<Vec<(f64, f64)>>,
+    colors: Option<Vec<Color>>,
+    indices: Vec<[usize; 3]>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ScalarType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl ScalarType {
+    fn parse(name: &str) -> Result<ScalarType> {
+        Ok(match name {
+            "char" | "int8" => ScalarType::I8,
+            "uchar" | "uint8" => ScalarType::U8,
+            "short" | "int16" => ScalarType::I16,
+            "ushort" | "uint16" => ScalarType::U16,
+            "int" | "int32" => ScalarType::I32,
+            "uint" | "uint32" => ScalarType::U32,
+            "float" | "float32" => ScalarType::F32,
+            "double" | "float64" => ScalarType::F64,
+            _ => bail!("unknown property type `{name}`"),
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            ScalarType::I8 | ScalarType::U8 => 1,
+            ScalarType::I16 | ScalarType::U16 => 2,
+            ScalarType::I32 | ScalarType::U32 | ScalarType::F32 => 4,
+            ScalarType::F64 => 8,
+        }
+    }
+
+    /// Factor to bring a color component of this type into [0,1]
+    fn color_scale(self) -> f64 {
+        match self {
+            ScalarType::U8 => 1.0 / 255.0,
+            ScalarType::U16 => 1.0 / 65535.0,
+            _ => 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Property {
+    Scalar {
+        name: String,
+        ty: ScalarType,
+    },
+    List {
+        name: String,
+        count_ty: ScalarType,
+        item_ty: ScalarType,
+    },
+}
+
+impl Property {
+    fn name(&self) -> &str {
+        match self {
+            Property::Scalar { name, .. } | Property::List { name, .. } => name,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+impl Element {
+    fn position(&self, names: &[&str]) -> Option<usize> {
+        self.properties
+            .iter()
+            .position(|property| names.contains(&property.name()))
+    }
+}
+
+/// Parses the header and returns the format, the elements and the offset of the body.
+fn parse_header(bytes: &[u8]) -> Result<(Format, Vec<Element>, usize)> {
+    let mut format = None;
+    let mut elements = Vec::<Element>::new();
+    let mut offset = 0;
+
+    for line_number in 1.. {
+        let Some(length) = bytes[offset..].iter().position(|&b| b == b'\n') else {
+            bail!("missing `end_header`");
+        };
+        let line = std::str::from_utf8(&bytes[offset..offset + length])
+            .map_err(|_| anyhow!("line {line_number}: header is not ASCII"))?;
+        offset += length + 1;
+
+        let mut tokens = line.split_ascii_whitespace();
+        let keyword = tokens.next();
+        if line_number == 1 {
+            if keyword != Some("ply") {
+                bail!("not a PLY file");
+            }
+            continue;
+        }
+        match keyword {
+            Some("format") => {
+                format = Some(match (tokens.next(), tokens.next()) {
+                    (Some("ascii"), Some("1.0")) => Format::Ascii,
+                    (Some("binary_little_endian"), Some("1.0")) => Format::BinaryLittleEndian,
+                    (Some("binary_big_endian"), Some("1.0")) => Format::BinaryBigEndian,
+                    _ => bail!("line {line_number}: unsupported format `{line}`"),
+                });
+            }
+            Some("element") => {
+                let (Some(name), Some(count)) = (tokens.next(), tokens.next()) else {
+                    bail!("line {line_number}: expected `element <name> <count>`");
+                };
+                let count = count
+                    .parse()
+                    .map_err(|_| anyhow!("line {line_number}: invalid element count `{count}`"))?;
+                elements.push(Element {
+                    name: name.to_string(),
+                    count,
+                    properties: vec![],
+                });
+            }
+            Some("property") => {
+                let Some(element) = elements.last_mut() else {
+                    bail!("line {line_number}: property appears before any element");
+                };
+                let tokens = tokens.collect::<Vec<_>>();
+                let property = match tokens.as_slice() {
+                    ["list", count_ty, item_ty, name] => Property::List {
+                        name: name.to_string(),
+                        count_ty: ScalarType::parse(count_ty)
+                            .with_context(|| format!("line {line_number}"))?,
+                        item_ty: ScalarType::parse(item_ty)
+                            .with_context(|| format!("line {line_number}"))?,
+                    },
+                    [ty, name] => Property::Scalar {
+                        name: name.to_string(),
+                        ty: ScalarType::parse(ty).with_context(|| format!("line {line_number}"))?,
+                    },
+                    _ => bail!("line {line_number}: invalid property `{line}`"),
+                };
+                element.properties.push(property);
+            }
+            Some("end_header") => break,
+            Some("comment") | Some("obj_info") | None => {}
+            Some(keyword) => bail!("line {line_number}: unknown keyword `{keyword}`"),
+        }
+    }
+
+    let Some(format) = format else {
+        bail!("missing `format`");
+    };
+    Ok((format, elements, offset))
+}
+
+enum Body<'a> {
+    Ascii(SplitAsciiWhitespace<'a>),
+    Binary {
+        bytes: &'a [u8],
+        offset: usize,
+        big_endian: bool,
+    },
+}
+
+impl<'a> Body<'a> {
+    fn read(&mut self, ty: ScalarType) -> Result<f64> {
+        match self {
+            Body::Ascii(tokens) => {
+                let token = tokens
+                    .next()
+                    .ok_or_else(|| anyhow!("unexpected end of file"))?;
+                token
+                    .parse::<f64>()
+                    .map_err(|_| anyhow!("invalid number `{token}`"))
+            }
+            Body::Binary {
+                bytes,
+                offset,
+                big_endian,
+            } => {
+                let size = ty.size();
+                let Some(data) = bytes.get(*offset..*offset + size) else {
+                    bail!("unexpected end of file");
+                };
+                *offset += size;
+                let mut buf = [0u8; 8];
+                buf[..size].copy_from_slice(data);
+                if *big_endian {
+                    buf[..size].reverse();
+                }
+                Ok(match ty {
+                    ScalarType::I8 => buf[0] as i8 as f64,
+                    ScalarType::U8 => buf[0] as f64,
+                    ScalarType::I16 => i16::from_le_bytes([buf[0], buf[1]]) as f64,
+                    ScalarType::U16 => u16::from_le_bytes([buf[0], buf[1]]) as f64,
+                    ScalarType::I32 => i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+                    ScalarType::U32 => u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+                    ScalarType::F32 => f32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+                    ScalarType::F64 => f64::from_le_bytes(buf),
+                })
+            }
+        }
+    }
+
+    /// Reads one property into `values`, replacing its contents.
+    fn read_property(&mut self, property: &Property, values: &mut Vec<f64>) -> Result<()> {
+        values.clear();
+        match property {
+            Property::Scalar { ty, .. } => values.push(self.read(*ty)?),
+            Property::List {
+                count_ty, item_ty, ..
+            } => {
+                let count = self.read(*count_ty)?;
+                if count < 0.0 {
+                    bail!("negative list length {count}");
+                }
+                for _ in 0..count as usize {
+                    values.push(self.read(*item_ty)?);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_ply(bytes: &[u8]) -> Result<Ply> {
+    let (format, elements, offset) = parse_header(bytes)?;
+    let mut body = match format {
+        Format::Ascii => Body::Ascii(
+            std::str::from_utf8(&bytes[offset..])
+                .map_err(|_| anyhow!("body is not ASCII"))?
+                .split_ascii_whitespace(),
+        ),
+        Format::BinaryLittleEndian | Format::BinaryBigEndian => Body::Binary {
+            bytes,
+            offset,
+            big_endian: format == Format::BinaryBigEndian,
+        },
+    };
+
+    let mut ply = Ply::default();
+    let mut values = Vec::<f64>::new();
+    for element in &elements {
+        match element.name.as_str() {
+            "vertex" => read_vertices(&mut body, element, &mut ply, &mut values)?,
+            "face" => read_faces(&mut body, element, &mut ply, &mut values)?,
+            _ => {
+                // Skip elements we have no use for
+                for i in 0..element.count {
+                    for property in &element.properties {
+                        body.read_property(property, &mut values)
+                            .with_context(|| format!("{} {i}", element.name))?;
+                    }
+                }
+            }
+        }
+    }
+
+    if ply.indices.is_empty() {
+        bail!("no faces found");
+    }
+    let vertex_count = ply.positions.len();
+    for (i, face) in ply.indices.iter().enumerate() {
+        if let Some(index) = face.iter().find(|&&index| index >= vertex_count) {
+            bail!("face {i}: vertex index {index} is out of range");
+        }
+    }
+    Ok(ply)
+}
+
+fn read_vertices(
+    body: &mut Body,
+    element: &Element,
+    ply: &mut Ply,
+    values: &mut Vec<f64>,
+) -> Result<()> {
+    let columns = |names: &[&[&str]]| {
+        names
+            .iter()
+            .map(|names| element.position(names))
+            .collect::<Option<Vec<_>>>()
+    };
+    let Some(position) = columns(&[&["x"], &["y"], &["z"]]) else {
+        bail!("vertex element needs x, y and z");
+    };
+    let normal = columns(&[&["nx"], &["ny"], &["nz"]]);
+    let uv = columns(&[
+        &["u", "s", "texture_u", "texture_s"],
+        &["v", "t", "texture_v", "texture_t"],
+    ]);
+    let color = columns(&[&["red"], &["green"], &["blue"]]);
+    let color_scales = color.as_ref().map(|color| {
+        color
+            .iter()
+            .map(|&i| match element.properties[i] {
+                Property::Scalar { ty, .. } => ty.color_scale(),
+                Property::List { .. } => 1.0,
+            })
+            .collect::<Vec<_>>()
+    });
+
+    // Where each property goes in a record of [x, y, z, nx, ny, nz, u, v, r, g, b]
+    let mut slots = vec![None; element.properties.len()];
+    for (base, columns) in [
+        (0, Some(&position)),
+        (3, normal.as_ref()),
+        (6, uv.as_ref()),
+        (8, color.as_ref()),
+    ] {
+        for (k, &column) in columns.into_iter().flatten().enumerate() {
+            slots[column] = Some(base + k);
+        }
+    }
+
+    // Not preallocated from the count in the header, which may be anything
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    let mut colors = Vec::new();
+    for i in 0..element.count {
+        let mut record = [0.0; 11];
+        for (property, slot) in element.properties.iter().zip(&slots) {
+            body.read_property(property, values)
+                .with_context(|| format!("vertex {i}"))?;
+            if let (Some(slot), Some(&value)) = (slot, values.first()) {
+                record[*slot] = value;
+            }
+        }
+        ply.positions
+            .push(Point3::new(record[0], record[1], record[2]));
+        normals.push(Vec3::new(record[3], record[4], record[5]));
+        uvs.push((record[6], record[7]));
+        if let Some(scales) = &color_scales {
+            colors.push(Color::new(
+                record[8] * scales[0],
+                record[9] * scales[1],
+                record[10] * scales[2],
+            ));
+        }
+    }
+
+    if normal.is_some() {
+        ply.normals = Some(normals);
+    }
+    if uv.is_some() {
+        ply.uvs = Some(uvs);
+    }
+    if color.is_some() {
+        ply.colors = Some(colors);
+    }
+    Ok(())
+}
+
+fn read_faces(
+    body: &mut Body,
+    element: &Element,
+    ply: &mut Ply,
+    values: &mut Vec<f64>,
+) -> Result<()> {
+    let Some(column) = element.position(&["vertex_indices", "vertex_index"]) else {
+        bail!("face element needs vertex_indices");
+    };
+
+    for i in 0..element.count {
+        for (j, property) in element.properties.iter().enumerate() {
+            body.read_property(property, values)
+                .with_context(|| format!("face {i}"))?;
+            if j != column {
+                continue;
+            }
+            if values.len() < 3 {
+                bail!("face {i}: a face needs at least 3 vertices");
+            }
+            if let Some(value) = values
+                .iter()
+                .find(|&&value| !(value >= 0.0 && value.fract() == 0.0))
+            {
+                bail!("face {i}: invalid vertex index {value}");
+            }
+            // Triangulate polygons as a fan around the first vertex
+            for k in 1..values.len() - 1 {
+                ply.indices.push([
+                    values[0] as usize,
+                    values[k] as usize,
+                    values[k + 1] as usize,
+                ]);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ascii() {
+        let source = "ply
+format ascii 1.0
+comment unit square
+element vertex 4
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0 255 0 0
+1 0 0 0 255 0
+1 1 0 0 0 255
+0 1 0 255 255 255
+4 0 1 2 3
+";
+        let ply = parse_ply(source.as_bytes()).unwrap();
+
+        assert_eq!(4, ply.positions.len());
+        assert_eq!(Point3::new(1.0, 1.0, 0.0), ply.positions[2]);
+        assert_eq!(vec![[0, 1, 2], [0, 2, 3]], ply.indices);
+        assert_eq!(
+            Some(Color::new(0.0, 1.0, 0.0)),
+            ply.colors.unwrap().get(1).copied()
+        );
+        assert!(ply.normals.is_none());
+        assert!(ply.uvs.is_none());
+    }
+
+    #[test]
+    fn test_parse_binary() {
+        fn push_f32(bytes: &mut Vec<u8>, values: &[f32], big_endian: bool) {
+            for v in values {
+                if big_endian {
+                    bytes.extend(v.to_be_bytes());
+                } else {
+                    bytes.extend(v.to_le_bytes());
+                }
+            }
+        }
+
+        for (format, big_endian) in [("binary_little_endian", false), ("binary_big_endian", true)] {
+            let mut bytes = format!(
+                "ply\nformat {format} 1.0\nelement vertex 3\nproperty float x\nproperty float y\n\
+                 property float z\nproperty float nx\nproperty float ny\nproperty float nz\n\
+                 element face 1\nproperty list uchar uint vertex_indices\nend_header\n"
+            )
+            .into_bytes();
+            push_f32(&mut bytes, &[0.0, 0.0, 0.0, 0.0, 0.0, 1.0], big_endian);
+            push_f32(&mut bytes, &[1.0, 0.0, 0.0, 0.0, 0.0, 1.0], big_endian);
+            push_f32(&mut bytes, &[0.0, 1.0, 0.0, 0.0, 0.0, 1.0], big_endian);
+            bytes.push(3);
+            for i in [0u32, 1, 2] {
+                if big_endian {
+                    bytes.extend(i.to_be_bytes());
+                } else {
+                    bytes.extend(i.to_le_bytes());
+                }
+            }
+            let ply = parse_ply(&bytes).unwrap();
+
+            assert_eq!(Point3::new(1.0, 0.0, 0.0), ply.positions[1]);
+            assert_eq!(
+                Some(Vec3::new(0.0, 0.0, 1.0)),
+                ply.normals.unwrap().get(2).copied()
+            );
+            assert_eq!(vec![[0, 1, 2]], ply.indices);
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |source: &str| match parse_ply(source.as_bytes()) {
+            Ok(_) => panic!("parse_ply should fail"),
+            Err(error) => format!("{error:#}"),
+        };
+        let header = "ply\nformat ascii 1.0\nelement vertex 3\nproperty float x\n\
+                      property float y\nproperty float z\nelement face 1\n\
+                      property list uchar int vertex_indices\nend_header\n";
+
+        assert_eq!("not a PLY file", error("obj\n"));
+        assert_eq!(
+            "line 4: unknown property type `half`",
+            error("ply\nformat ascii 1.0\nelement vertex 1\nproperty half x\n")
+        );
+        assert_eq!(
+            "vertex 2: unexpected end of file",
+            error(&format!("{header}0 0 0\n1 0 0\n0 1"))
+        );
+        assert_eq!(
+            "face 0: vertex index 3 is out of range",
+            error(&format!("{header}0 0 0\n1 0 0\n0 1 0\n3 0 1 3\n"))
+        );
+        assert_eq!(
+            "face 0: invalid vertex index -1",
+            error(&format!("{header}0 0 0\n1 0 0\n0 1 0\n3 -1 1 2\n"))
+        );
+        assert_eq!(
+            "face 0: invalid vertex index 1.5",
+            error(&format!("{header}0 0 0\n1 0 0\n0 1 0\n3 0 1.5 2\n"))
+        );
+        assert_eq!(
+            "vertex 0: unexpected end of file",
+            error(
+                "ply\nformat ascii 1.0\nelement vertex 99999999999999\nproperty float x\n\
+                   property float y\nproperty float z\nend_header\n"
+            )
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use crate::{hittables::TriangleMesh, materials::Material, vec3::Point3};
+
+/// Loads an ASCII or binary STL file as a mesh with a single material.
+///
+/// Facets are joined at identical vertex positions; facet normals in the file are ignored.
+pub fn load_stl<M: Material + 'static>(
+    filename: impl AsRef<Path>,
+    material: M,
+) -> Result<TriangleMesh<M>> {
+    let filename = filename.as_ref();
+    let bytes =
+        fs::read(filename).with_context(|| format!("failed to read {}", filename.display()))?;
+    let (positions, indices) =
+        parse_stl(&bytes).with_context(|| format!("failed to load {}", filename.display()))?;
+    Ok(TriangleMesh::new(positions, indices, material))
+}
+
+fn parse_stl(bytes: &[u8]) -> Result<(Vec<Point3>, Vec<[usize; 3]>)> {
+    let triangles = if is_binary(bytes) {
+        parse_binary(bytes)
+    } else if bytes.starts_with(b"solid") {
+        parse_ascii(bytes)?
+    } else {
+        bail!("not an STL file");
+    };
+    if triangles.is_empty() {
+        bail!("no facets found");
+    }
+
+    let mut positions = Vec::<Point3>::new();
+    let mut vertices = HashMap::<[u64; 3], usize>::new();
+    let indices = triangles
+        .into_iter()
+        .map(|triangle| {
+            triangle.map(|p| {
+                let key = [p.x().to_bits(), p.y().to_bits(), p.z().to_bits()];
+                *vertices.entry(key).or_insert_with(|| {
+                    positions.push(p);
+                    positions.len() - 1
+                })
+            })
+        })
+        .collect();
+    Ok((positions, indices))
+}
+
+/// Binary files may also begin with `solid`, so the size is checked against the facet count.
+fn is_binary(bytes: &[u8]) -> bool {
+    let Some(count) = bytes.get(80..84) else {
+        return false;
+    };
+    let count = u32::from_le_bytes([count[0], count[1], count[2], count[3]]) as usize;
+    bytes.len() == 84 + 50 * count
+}
+
+fn parse_binary(bytes: &[u8]) -> Vec<[Point3; 3]> {
+    let read = |b: &[u8]| f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64;
+    bytes[84..]
+        .chunks_exact(50)
+        .map(|facet| {
+            // Each facet is a normal, three vertices and a 2-byte attribute
+            [12, 24, 36].map(|offset| {
+                Point3::new(
+                    read(&facet[offset..]),
+                    read(&facet[offset + 4..]),
+                    read(&facet[offset + 8..]),
+                )
+            })
+        })
+        .collect()
+}
+
+fn parse_ascii(bytes: &[u8]) -> Result<Vec<[Point3; 3]>> {
+    let source = std::str::from_utf8(bytes).map_err(|_| anyhow!("file is not ASCII"))?;
+    let mut triangles = Vec::<[Point3; 3]>::new();
+    let mut vertices = Vec::<Point3>::new();
+
+    for (line_number, line) in source.lines().enumerate() {
+        let line_number = line_number + 1;
+        let mut tokens = line.split_ascii_whitespace();
+        match tokens.next() {
+            Some("vertex") => {
+                let coordinates = tokens
+                    .map(|token| {
+                        token
+                            .parse::<f64>()
+                            .map_err(|_| anyhow!("line {line_number}: invalid number `{token}`"))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                let [x, y, z] = coordinates[..] else {
+                    bail!("line {line_number}: a vertex needs 3 coordinates");
+                };
+                vertices.push(Point3::new(x, y, z));
+            }
+            Some("endfacet") => {
+                let [v0, v1, v2] = vertices[..] else {
+                    bail!(
+                        "line {line_number}: a facet needs 3 vertices, found {}",
+                        vertices.len()
+                    );
+                };
+                triangles.push([v0, v1, v2]);
+                vertices.clear();
+            }
+            Some("solid" | "facet" | "outer" | "endloop" | "endsolid") | None => {}
+            Some(keyword) => bail!("line {line_number}: unknown keyword `{keyword}`"),
+        }
+    }
+
+    Ok(triangles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ascii() {
+        let source = "solid square
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 0
+      vertex 1 0 0
+      vertex 1 1 0
+    endloop
+  endfacet
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 0
+      vertex 1 1 0
+      vertex 0 1 0
+    endloop
+  endfacet
+endsolid square
+";
+        let (positions, indices) = parse_stl(source.as_bytes()).unwrap();
+
+        assert_eq!(4, positions.len());
+        assert_eq!(vec![[0, 1, 2], [0, 2, 3]], indices);
+        assert_eq!(Point3::new(0.0, 1.0, 0.0), positions[3]);
+    }
+
+    #[test]
+    fn test_parse_binary() {
+        // A header starting with "solid" must not be mistaken for an ASCII file
+        let mut bytes = b"solid".to_vec();
+        bytes.resize(80, 0);
+        bytes.extend(1u32.to_le_bytes());
+        for v in [
+            0.0f32, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0,
+        ] {
+            bytes.extend(v.to_le_bytes());
+        }
+        bytes.extend([0, 0]);
+        let (positions, indices) = parse_stl(&bytes).unwrap();
+
+        assert_eq!(
+            vec![
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(1.0, 0.0, 0.0),
+                Point3::new(0.0, 1.0, 0.0)
+            ],
+            positions
+        );
+        assert_eq!(vec![[0, 1, 2]], indices);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |source: &str| match parse_stl(source.as_bytes()) {
+            Ok(_) => panic!("parse_stl should fail"),
+            Err(error) => format!("{error:#}"),
+        };
+
+        assert_eq!("not an STL file", error("ply\n"));
+        assert_eq!(
+            "line 3: invalid number `a`",
+            error("solid\nfacet normal 0 0 1\nvertex 0 0 a\n")
+        );
+        assert_eq!(
+            "line 4: a facet needs 3 vertices, found 1",
+            error("solid\nfacet normal 0 0 1\nvertex 0 0 0\nendfacet\n")
+        );
+    }
+}
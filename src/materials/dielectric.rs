@@ -2,6 +2,7 @@ use rand::Rng;
 
 use crate::{
     hittables::Hit,
+    random,
     ray::Ray,
     vec3::{Color, Vec3},
 };
@@ -48,7 +49,7 @@ impl Material for Dielectric {
         let cos_theta = (-unit_direction.dot(hit.normal)).min(1.0);
         let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
         let cannot_refract = refraction_ratio * sin_theta > 1.0;
-        let mut rng = rand::thread_rng();
+        let mut rng = random::rng();
         let reflectance = Dielectric::reflectance(cos_theta, refraction_ratio);
         let should_reflect = reflectance > rng.gen();
         let direction = if cannot_refract || should_reflect {
@@ -1,3 +1,5 @@
+use std::f64::consts::PI;
+
 use crate::{
     hittables::Hit,
     ray::Ray,
@@ -27,13 +29,21 @@ impl<T: Texture> Lambertian<T> {
 
 impl<T: Texture> Material for Lambertian<T> {
     fn scatter(&self, ray: &Ray, hit: &Hit) -> Option<Scatter> {
+        // Cosine-weighted around the normal
         let mut scatter_direction = hit.normal + Vec3::random_in_unit_sphere().unit();
         if scatter_direction.is_near_zero() {
             scatter_direction = hit.normal;
         }
-        Some(Scatter {
-            attenuation: self.albedo.value(hit.u, hit.v, &hit.p),
-            ray: Ray::new(hit.p, scatter_direction, ray.time),
-        })
+        let scattered = Ray::new(hit.p, scatter_direction, ray.time);
+        Some(Scatter::new_with_pdf(
+            self.albedo.value_at(hit),
+            scattered,
+            self.scattering_pdf(ray, hit, &scattered),
+        ))
+    }
+
+    fn scattering_pdf(&self, _ray: &Ray, hit: &Hit, scattered: &Ray) -> f64 {
+        let cosine = hit.normal.dot(scattered.direction.unit());
+        (cosine / PI).max(0.0)
     }
 }
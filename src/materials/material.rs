@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use crate::{
     hittables::Hit,
     ray::Ray,
@@ -9,8 +11,32 @@ use super::Scatter;
 pub trait Material: Send + Sync {
     fn scatter(&self, ray: &Ray, hit: &Hit) -> Option<Scatter>;
 
+    /// Density over solid angle with which `scatter` picks the direction of `scattered`.
+    ///
+    /// For a scattering that is not specular, the light arriving from that direction is reflected
+    /// along `ray` weighted by `attenuation * scattering_pdf`, whichever direction was sampled.
+    /// Specular materials return zero.
+    #[allow(unused_variables)]
+    fn scattering_pdf(&self, ray: &Ray, hit: &Hit, scattered: &Ray) -> f64 {
+        0.0
+    }
+
     #[allow(unused_variables)]
     fn emitted(&self, u: f64, v: f64, p: &Point3) -> Color {
         Color::default()
     }
 }
+
+impl<M: Material + ?Sized> Material for Arc<M> {
+    fn scatter(&self, ray: &Ray, hit: &Hit) -> Option<Scatter> {
+        (**self).scatter(ray, hit)
+    }
+
+    fn scattering_pdf(&self, ray: &Ray, hit: &Hit, scattered: &Ray) -> f64 {
+        (**self).scattering_pdf(ray, hit, scattered)
+    }
+
+    fn emitted(&self, u: f64, v: f64, p: &Point3) -> Color {
+        (**self).emitted(u, v, p)
+    }
+}
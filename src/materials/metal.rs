@@ -1,3 +1,5 @@
+use std::f64::consts::PI;
+
 use crate::{
     hittables::Hit,
     ray::Ray,
@@ -21,21 +23,93 @@ impl Metal {
     }
 }
 
+fn reflect(v: Vec3, n: Vec3) -> Vec3 {
+    v - 2.0 * v.dot(n) * n
+}
+
 impl Material for Metal {
     fn scatter(&self, ray: &Ray, hit: &Hit) -> Option<Scatter> {
-        fn reflect(v: Vec3, n: Vec3) -> Vec3 {
-            v - 2.0 * v.dot(n) * n
-        }
         let reflected = reflect(ray.direction.unit(), hit.normal);
         let scattered = Ray::new(
             hit.p,
             reflected + self.fuzziness * Vec3::random_in_unit_sphere(),
             ray.time,
         );
-        if scattered.direction.dot(hit.normal) > 0.0 {
-            Some(Scatter::new(self.albedo, scattered))
-        } else {
+        if scattered.direction.dot(hit.normal) <= 0.0 {
             None
+        } else if self.fuzziness > 0.0 {
+            Some(Scatter::new_with_pdf(
+                self.albedo,
+                scattered,
+                self.scattering_pdf(ray, hit, &scattered),
+            ))
+        } else {
+            Some(Scatter::new(self.albedo, scattered))
+        }
+    }
+
+    /// The directions from the origin of the points in the ball of radius `fuzziness` around the
+    /// reflected direction, whose density is the volume of the ball within a cone.
+    fn scattering_pdf(&self, ray: &Ray, hit: &Hit, scattered: &Ray) -> f64 {
+        let direction = scattered.direction.unit();
+        if self.fuzziness <= 0.0 || direction.dot(hit.normal) <= 0.0 {
+            return 0.0;
         }
+        let reflected = reflect(ray.direction.unit(), hit.normal);
+        // The ray along the direction is within the ball between the distances `c ± s`
+        let c = direction.dot(reflected);
+        let discriminant = c * c - (1.0 - self.fuzziness * self.fuzziness);
+        if c <= 0.0 || discriminant <= 0.0 {
+            return 0.0;
+        }
+        let s = discriminant.sqrt();
+        // ((c + s)³ - (c - s)³) / 3 over the volume of the ball
+        2.0 * s * (3.0 * c * c + s * s) / (4.0 * PI * self.fuzziness.powi(3))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hittables::{Hittable, XzRect},
+        materials::Lambertian,
+        random,
+        vec3::Point3,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_scattering_pdf_integrates_to_one() {
+        let floor = XzRect::new(
+            -1.0,
+            1.0,
+            -1.0,
+            1.0,
+            0.0,
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        );
+        let ray = Ray::new(Point3::new(-1.0, 1.0, 0.0), Vec3::new(1.0, -2.0, 0.0), 0.0);
+        let hit = floor.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        random::seed(1);
+
+        // The ball is entirely above the surface for these
+        for fuzziness in [0.4, 0.8] {
+            let metal = Metal::new(Color::new(0.8, 0.8, 0.8), fuzziness);
+            let n = 400_000;
+            let integral = (0..n)
+                .map(|_| {
+                    let scattered = Ray::new(hit.p, Vec3::random_in_unit_sphere(), 0.0);
+                    metal.scattering_pdf(&ray, &hit, &scattered)
+                })
+                .sum::<f64>()
+                * 4.0
+                * PI
+                / n as f64;
+            assert!((integral - 1.0).abs() < 0.03);
+        }
+
+        let mirror = Metal::new(Color::new(0.8, 0.8, 0.8), 0.0);
+        assert!(mirror.scatter(&ray, &hit).unwrap().pdf.is_none());
     }
 }
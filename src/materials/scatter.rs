@@ -5,10 +5,28 @@ use crate::{ray::Ray, vec3::Color};
 pub struct Scatter {
     pub attenuation: Color,
     pub ray: Ray,
+    /// Density over solid angle of the direction of `ray`, or `None` if the scattering is
+    /// specular and the direction cannot be importance sampled
+    pub pdf: Option<f64>,
 }
 
 impl Scatter {
+    /// Specular scattering in a single direction.
     pub fn new(attenuation: Color, ray: Ray) -> Scatter {
-        Scatter { attenuation, ray }
+        Scatter {
+            attenuation,
+            ray,
+            pdf: None,
+        }
+    }
+
+    /// Scattering in a direction picked with the density `pdf`, which is the `scattering_pdf` of
+    /// the material.
+    pub fn new_with_pdf(attenuation: Color, ray: Ray, pdf: f64) -> Scatter {
+        Scatter {
+            attenuation,
+            ray,
+            pdf: Some(pdf),
+        }
     }
 }
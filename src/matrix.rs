@@ -0,0 +1,248 @@
+use std::ops;
+
+use crate::vec3::{Point3, Vec3};
+
+/// 4x4 matrix of an affine transform in row-major order, applied to column vectors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Matrix4 {
+    m: [[f64; 4]; 4],
+}
+
+impl Matrix4 {
+    pub fn new(m: [[f64; 4]; 4]) -> Matrix4 {
+        Matrix4 { m }
+    }
+
+    pub fn identity() -> Matrix4 {
+        Matrix4::scaling(Vec3::new(1.0, 1.0, 1.0))
+    }
+
+    pub fn translation(offset: Vec3) -> Matrix4 {
+        Matrix4::new([
+            [1.0, 0.0, 0.0, offset.x()],
+            [0.0, 1.0, 0.0, offset.y()],
+            [0.0, 0.0, 1.0, offset.z()],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn scaling(scale: Vec3) -> Matrix4 {
+        Matrix4::new([
+            [scale.x(), 0.0, 0.0, 0.0],
+            [0.0, scale.y(), 0.0, 0.0],
+            [0.0, 0.0, scale.z(), 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Rotation by `theta` radians around `axis`, counter-clockwise when the axis points to the
+    /// viewer.
+    pub fn rotation(axis: Vec3, theta: f64) -> Matrix4 {
+        let a = axis.unit();
+        let (x, y, z) = (a.x(), a.y(), a.z());
+        let (s, c) = theta.sin_cos();
+        let t = 1.0 - c;
+        Matrix4::new([
+            [t * x * x + c, t * x * y - s * z, t * x * z + s * y, 0.0],
+            [t * x * y + s * z, t * y * y + c, t * y * z - s * x, 0.0],
+            [t * x * z - s * y, t * y * z + s * x, t * z * z + c, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Shear where `xy` is the amount of `x` added per unit of `y`, and so on.
+    pub fn shearing(xy: f64, xz: f64, yx: f64, yz: f64, zx: f64, zy: f64) -> Matrix4 {
+        Matrix4::new([
+            [1.0, xy, xz, 0.0],
+            [yx, 1.0, yz, 0.0],
+            [zx, zy, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn transpose(&self) -> Matrix4 {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.m[j][i];
+            }
+        }
+        Matrix4::new(m)
+    }
+
+    /// Inverse by Gauss-Jordan elimination, or `None` if the matrix is singular or not finite.
+    pub fn inverse(&self) -> Option<Matrix4> {
+        let mut a = self.m;
+        let mut inverse = Matrix4::identity().m;
+        for column in 0..4 {
+            // Partial pivoting for numerical stability
+            let pivot = (column..4)
+                .max_by(|&i, &j| a[i][column].abs().total_cmp(&a[j][column].abs()))
+                .unwrap();
+            if !a[pivot][column].is_finite() || a[pivot][column].abs() < 1e-12 {
+                return None;
+            }
+            a.swap(column, pivot);
+            inverse.swap(column, pivot);
+
+            let scale = 1.0 / a[column][column];
+            for j in 0..4 {
+                a[column][j] *= scale;
+                inverse[column][j] *= scale;
+            }
+            for i in 0..4 {
+                if i == column {
+                    continue;
+                }
+                let factor = a[i][column];
+                for j in 0..4 {
+                    a[i][j] -= factor * a[column][j];
+                    inverse[i][j] -= factor * inverse[column][j];
+                }
+            }
+        }
+        Some(Matrix4::new(inverse))
+    }
+
+    /// The inverse transpose, which transforms normals so that they stay perpendicular to
+    /// transformed surfaces.
+    pub fn normal_matrix(&self) -> Option<Matrix4> {
+        self.inverse().map(|inverse| inverse.transpose())
+    }
+
+    pub fn transform_point(&self, p: Point3) -> Point3 {
+        let m = &self.m;
+        Point3::new(
+            m[0][0] * p.x() + m[0][1] * p.y() + m[0][2] * p.z() + m[0][3],
+            m[1][0] * p.x() + m[1][1] * p.y() + m[1][2] * p.z() + m[1][3],
+            m[2][0] * p.x() + m[2][1] * p.y() + m[2][2] * p.z() + m[2][3],
+        )
+    }
+
+    /// Transforms a direction, ignoring the translation.
+    pub fn transform_vector(&self, v: Vec3) -> Vec3 {
+        let m = &self.m;
+        Vec3::new(
+            m[0][0] * v.x() + m[0][1] * v.y() + m[0][2] * v.z(),
+            m[1][0] * v.x() + m[1][1] * v.y() + m[1][2] * v.z(),
+            m[2][0] * v.x() + m[2][1] * v.y() + m[2][2] * v.z(),
+        )
+    }
+}
+
+impl Default for Matrix4 {
+    fn default() -> Matrix4 {
+        Matrix4::identity()
+    }
+}
+
+impl ops::Index<usize> for Matrix4 {
+    type Output = [f64; 4];
+
+    fn index(&self, row: usize) -> &[f64; 4] {
+        &self.m[row]
+    }
+}
+
+/// `a * b` applies `b` first, then `a`.
+impl ops::Mul for Matrix4 {
+    type Output = Matrix4;
+
+    fn mul(self, rhs: Matrix4) -> Matrix4 {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.m[i][k] * rhs.m[k][j]).sum();
+            }
+        }
+        Matrix4::new(m)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::*;
+
+    fn assert_near(expected: Vec3, actual: Vec3) {
+        assert!(
+            (expected - actual).length() < 1e-10,
+            "expected {expected}, found {actual}"
+        );
+    }
+
+    #[test]
+    fn test_transform() {
+        let m = Matrix4::translation(Vec3::new(1.0, 2.0, 3.0))
+            * Matrix4::rotation(Vec3::new(0.0, 0.0, 1.0), PI / 2.0)
+            * Matrix4::scaling(Vec3::new(2.0, 2.0, 2.0));
+
+        assert_near(
+            Point3::new(1.0, 4.0, 3.0),
+            m.transform_point(Point3::new(1.0, 0.0, 0.0)),
+        );
+        assert_near(
+            Vec3::new(0.0, 2.0, 0.0),
+            m.transform_vector(Vec3::new(1.0, 0.0, 0.0)),
+        );
+    }
+
+    #[test]
+    fn test_rotation_y_matches_rotate_y() {
+        let theta = 0.3;
+        let m = Matrix4::rotation(Vec3::new(0.0, 1.0, 0.0), theta);
+        let p = Point3::new(1.0, 2.0, 3.0);
+
+        assert_near(
+            Point3::new(
+                theta.cos() * p.x() + theta.sin() * p.z(),
+                p.y(),
+                -theta.sin() * p.x() + theta.cos() * p.z(),
+            ),
+            m.transform_point(p),
+        );
+    }
+
+    #[test]
+    fn test_inverse() {
+        let m = Matrix4::translation(Vec3::new(1.0, -2.0, 3.0))
+            * Matrix4::rotation(Vec3::new(1.0, 1.0, 0.0), 0.7)
+            * Matrix4::scaling(Vec3::new(2.0, 0.5, 3.0));
+        let inverse = m.inverse().unwrap();
+        let p = Point3::new(0.3, -0.2, 5.0);
+
+        assert_near(p, inverse.transform_point(m.transform_point(p)));
+        let identity = m * inverse;
+        for i in 0..4 {
+            for j in 0..4 {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((identity[i][j] - expected).abs() < 1e-10);
+            }
+        }
+
+        assert_eq!(None, Matrix4::scaling(Vec3::new(1.0, 0.0, 1.0)).inverse());
+        assert_eq!(
+            None,
+            Matrix4::scaling(Vec3::new(1.0, f64::NAN, 1.0)).inverse()
+        );
+        assert_eq!(
+            None,
+            Matrix4::translation(Vec3::new(f64::INFINITY, 0.0, 0.0)).inverse()
+        );
+    }
+
+    #[test]
+    fn test_normal_matrix() {
+        let m = Matrix4::shearing(1.0, 0.0, 0.0, 0.0, 0.0, 0.0)
+            * Matrix4::scaling(Vec3::new(1.0, 3.0, 1.0));
+        let normal_matrix = m.normal_matrix().unwrap();
+
+        // A plane through the origin, spanned by two tangents
+        let (tangent0, tangent1) = (Vec3::new(1.0, 1.0, 0.0), Vec3::new(0.0, 1.0, 1.0));
+        let normal = tangent0.cross(tangent1);
+        let transformed = normal_matrix.transform_vector(normal);
+        assert!(transformed.dot(m.transform_vector(tangent0)).abs() < 1e-10);
+        assert!(transformed.dot(m.transform_vector(tangent1)).abs() < 1e-10);
+    }
+}
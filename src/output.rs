@@ -0,0 +1,160 @@
+use std::io::{Cursor, Write};
+
+use anyhow::Result;
+use image::{
+    codecs::hdr::HdrEncoder, DynamicImage, ImageBuffer, ImageOutputFormat, Rgb, Rgb32FImage,
+};
+
+use crate::vec3::Color;
+
+/// Writes encoded colors in [0, 1] as ASCII PPM.
+pub fn write_ppm(w: &mut impl Write, image: &[Vec<Color>]) -> Result<()> {
+    let (width, height) = dimensions(image);
+    writeln!(w, "P3\n{width} {height}\n255")?;
+    for row in image {
+        for c in row {
+            c.write(w)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes encoded colors in [0, 1] as an 8-bit PNG.
+pub fn write_png8(w: &mut impl Write, image: &[Vec<Color>]) -> Result<()> {
+    let (width, height) = dimensions(image);
+    let buffer = ImageBuffer::from_fn(width, height, |i, j| {
+        let c = image[j as usize][i as usize];
+        // Same quantization as `Color::write`
+        Rgb([0, 1, 2].map(|k| (255.999 * c[k].clamp(0.0, 0.999)) as u8))
+    });
+    write_encoded(w, DynamicImage::ImageRgb8(buffer), ImageOutputFormat::Png)
+}
+
+/// Writes encoded colors in [0, 1] as a 16-bit PNG.
+pub fn write_png16(w: &mut impl Write, image: &[Vec<Color>]) -> Result<()> {
+    let (width, height) = dimensions(image);
+    let buffer = ImageBuffer::from_fn(width, height, |i, j| {
+        let c = image[j as usize][i as usize];
+        Rgb([0, 1, 2].map(|k| (65535.0 * c[k].clamp(0.0, 1.0)).round() as u16))
+    });
+    write_encoded(w, DynamicImage::ImageRgb16(buffer), ImageOutputFormat::Png)
+}
+
+/// Writes the linear radiance values as a 32-bit float OpenEXR image.
+pub fn write_exr(w: &mut impl Write, image: &[Vec<Color>]) -> Result<()> {
+    write_encoded(
+        w,
+        DynamicImage::ImageRgb32F(linear_image(image)),
+        ImageOutputFormat::OpenExr,
+    )
+}
+
+/// Writes the linear radiance values as a Radiance HDR (RGBE) image.
+pub fn write_hdr(w: &mut impl Write, image: &[Vec<Color>]) -> Result<()> {
+    let (width, height) = dimensions(image);
+    let pixels = linear_image(image).pixels().copied().collect::<Vec<_>>();
+    HdrEncoder::new(w).encode(&pixels, width as usize, height as usize)?;
+    Ok(())
+}
+
+fn dimensions(image: &[Vec<Color>]) -> (u32, u32) {
+    let height = image.len();
+    let width = image.first().map_or(0, |row| row.len());
+    (width as u32, height as u32)
+}
+
+fn linear_image(image: &[Vec<Color>]) -> Rgb32FImage {
+    let (width, height) = dimensions(image);
+    ImageBuffer::from_fn(width, height, |i, j| {
+        let c = image[j as usize][i as usize];
+        Rgb([c.x() as f32, c.y() as f32, c.z() as f32])
+    })
+}
+
+/// Encodes in memory first, since some encoders need to seek in their output.
+fn write_encoded(w: &mut impl Write, image: DynamicImage, format: ImageOutputFormat) -> Result<()> {
+    let mut buffer = Cursor::new(Vec::new());
+    image.write_to(&mut buffer, format)?;
+    w.write_all(buffer.get_ref())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{codecs::hdr::HdrDecoder, io::Reader};
+
+    use super::*;
+
+    fn image() -> Vec<Vec<Color>> {
+        vec![
+            vec![Color::new(0.5, 0.5, 0.75), Color::new(4.0, 0.0, 1.0)],
+            vec![Color::new(0.0, 0.0, 0.0), Color::new(1.0, 1.0, 1.0)],
+        ]
+    }
+
+    fn decode(bytes: Vec<u8>) -> DynamicImage {
+        Reader::new(Cursor::new(bytes))
+            .with_guessed_format()
+            .unwrap()
+            .decode()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_write_ppm() {
+        let mut buf = vec![];
+        write_ppm(&mut buf, &image()).unwrap();
+
+        assert_eq!(
+            "P3\n2 2\n255\n127 127 191\n255 0 255\n0 0 0\n255 255 255\n",
+            String::from_utf8_lossy(&buf)
+        );
+    }
+
+    #[test]
+    fn test_write_png8() {
+        let mut buf = vec![];
+        write_png8(&mut buf, &image()).unwrap();
+        let decoded = decode(buf).into_rgb8();
+
+        assert_eq!((2, 2), decoded.dimensions());
+        assert_eq!(&Rgb([127, 127, 191]), decoded.get_pixel(0, 0));
+        assert_eq!(&Rgb([255, 0, 255]), decoded.get_pixel(1, 0));
+    }
+
+    #[test]
+    fn test_write_png16() {
+        let mut buf = vec![];
+        write_png16(&mut buf, &image()).unwrap();
+        let decoded = decode(buf).into_rgb16();
+
+        assert_eq!(&Rgb([32768, 32768, 49151]), decoded.get_pixel(0, 0));
+        assert_eq!(&Rgb([65535, 65535, 65535]), decoded.get_pixel(1, 1));
+    }
+
+    #[test]
+    fn test_write_exr_keeps_radiance() {
+        let mut buf = vec![];
+        write_exr(&mut buf, &image()).unwrap();
+        let decoded = decode(buf).into_rgb32f();
+
+        assert_eq!(&Rgb([0.5, 0.5, 0.75]), decoded.get_pixel(0, 0));
+        assert_eq!(&Rgb([4.0, 0.0, 1.0]), decoded.get_pixel(1, 0));
+    }
+
+    #[test]
+    fn test_write_hdr_keeps_radiance() {
+        let mut buf = vec![];
+        write_hdr(&mut buf, &image()).unwrap();
+        // The generic decoder converts HDR images to 8 bits, so the HDR decoder is used directly
+        let decoded = HdrDecoder::new(Cursor::new(buf))
+            .unwrap()
+            .read_image_hdr()
+            .unwrap();
+
+        // RGBE shares one exponent among the channels, so small values lose some precision
+        let pixel = decoded[1];
+        assert_eq!(4.0, pixel[0]);
+        assert!((pixel[2] - 1.0).abs() < 0.02);
+    }
+}
@@ -0,0 +1,197 @@
+//! Real roots of polynomials up to the fourth degree, after Jochen Schwarze's solver in
+//! Graphics Gems I.
+
+use std::f64::consts::PI;
+
+const EPSILON: f64 = 1e-12;
+
+fn is_zero(x: f64) -> bool {
+    x.abs() < EPSILON
+}
+
+/// Whether `coefficient` vanishes next to the largest of `coefficients`, so that it does not
+/// depend on how the polynomial is scaled.
+fn is_negligible(coefficient: f64, coefficients: &[f64]) -> bool {
+    let scale = coefficients
+        .iter()
+        .fold(0.0, |scale: f64, c| scale.max(c.abs()));
+    coefficient.abs() <= EPSILON * scale
+}
+
+/// Real roots of `a x^2 + b x + c`, in no particular order.
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if is_negligible(a, &[a, b, c]) {
+        return if is_negligible(b, &[b, c]) {
+            vec![]
+        } else {
+            vec![-c / b]
+        };
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return vec![];
+    }
+    // Avoids the cancellation of `-b + sqrt(discriminant)` when `4ac` is small
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    if is_negligible(q, &[a, b, c]) {
+        return vec![0.0];
+    }
+    vec![q / a, c / q]
+}
+
+/// Real roots of `a x^3 + b x^2 + c x + d`, in no particular order.
+pub fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    if is_negligible(a, &[a, b, c, d]) {
+        return solve_quadratic(b, c, d);
+    }
+    // Normal form `x^3 + a x^2 + b x + c`
+    let (a, b, c) = (b / a, c / a, d / a);
+
+    // Substituting `x = y - a / 3` eliminates the quadratic term: `y^3 + 3 p y + 2 q`
+    let sq_a = a * a;
+    let p = (-sq_a / 3.0 + b) / 3.0;
+    let q = (2.0 / 27.0 * a * sq_a - a * b / 3.0 + c) / 2.0;
+    let cb_p = p * p * p;
+    let discriminant = q * q + cb_p;
+
+    let roots = if is_zero(discriminant) {
+        if is_zero(q) {
+            vec![0.0]
+        } else {
+            let u = (-q).cbrt();
+            vec![2.0 * u, -u]
+        }
+    } else if discriminant < 0.0 {
+        // Three real roots
+        let phi = (-q / (-cb_p).sqrt()).clamp(-1.0, 1.0).acos() / 3.0;
+        let t = 2.0 * (-p).sqrt();
+        vec![
+            t * phi.cos(),
+            -t * (phi + PI / 3.0).cos(),
+            -t * (phi - PI / 3.0).cos(),
+        ]
+    } else {
+        let sqrt_discriminant = discriminant.sqrt();
+        vec![(sqrt_discriminant - q).cbrt() - (sqrt_discriminant + q).cbrt()]
+    };
+    roots.into_iter().map(|y| y - a / 3.0).collect()
+}
+
+/// Real roots of `a x^4 + b x^3 + c x^2 + d x + e`, in no particular order.
+///
+/// The roots are polished by Newton's method, since the closed form loses precision.
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    if is_negligible(a, &[a, b, c, d, e]) {
+        return solve_cubic(b, c, d, e);
+    }
+    let coefficients = [a, b, c, d, e];
+    // Normal form `x^4 + a x^3 + b x^2 + c x + d`
+    let (a, b, c, d) = (b / a, c / a, d / a, e / a);
+
+    // Substituting `x = y - a / 4` eliminates the cubic term: `y^4 + p y^2 + q y + r`
+    let sq_a = a * a;
+    let p = -3.0 / 8.0 * sq_a + b;
+    let q = sq_a * a / 8.0 - a * b / 2.0 + c;
+    let r = -3.0 / 256.0 * sq_a * sq_a + sq_a * b / 16.0 - a * c / 4.0 + d;
+
+    let roots = if is_zero(r) {
+        // `y (y^3 + p y + q) = 0`
+        let mut roots = solve_cubic(1.0, 0.0, p, q);
+        roots.push(0.0);
+        roots
+    } else {
+        // Any real root of the resolvent cubic splits the quartic into two quadratics
+        let z = solve_cubic(1.0, -p / 2.0, -r, r * p / 2.0 - q * q / 8.0)[0];
+        let u = z * z - r;
+        let v = 2.0 * z - p;
+        let u = if is_zero(u) {
+            0.0
+        } else if u > 0.0 {
+            u.sqrt()
+        } else {
+            return vec![];
+        };
+        let v = if is_zero(v) {
+            0.0
+        } else if v > 0.0 {
+            v.sqrt()
+        } else {
+            return vec![];
+        };
+        let v = if q < 0.0 { -v } else { v };
+        let mut roots = solve_quadratic(1.0, v, z - u);
+        roots.extend(solve_quadratic(1.0, -v, z + u));
+        roots
+    };
+
+    roots
+        .into_iter()
+        .map(|y| polish(&coefficients, y - a / 4.0))
+        .collect()
+}
+
+/// A few steps of Newton's method for the polynomial with `coefficients` from the highest degree.
+fn polish(coefficients: &[f64], mut x: f64) -> f64 {
+    for _ in 0..2 {
+        let (value, derivative) = coefficients
+            .iter()
+            .fold((0.0, 0.0), |(value, derivative), &coefficient| {
+                (value * x + coefficient, derivative * x + value)
+            });
+        if derivative == 0.0 {
+            break;
+        }
+        x -= value / derivative;
+    }
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(mut roots: Vec<f64>) -> Vec<f64> {
+        roots.sort_by(f64::total_cmp);
+        roots
+    }
+
+    fn assert_roots(expected: &[f64], actual: Vec<f64>) {
+        let actual = sorted(actual);
+        assert_eq!(expected.len(), actual.len(), "{actual:?}");
+        for (expected, actual) in expected.iter().zip(&actual) {
+            assert!((expected - actual).abs() < 1e-9, "{actual:?}");
+        }
+    }
+
+    #[test]
+    fn test_solve_quadratic() {
+        assert_roots(&[-3.0, 2.0], solve_quadratic(1.0, 1.0, -6.0));
+        assert_roots(&[], solve_quadratic(1.0, 0.0, 1.0));
+        assert_roots(&[-0.5], solve_quadratic(0.0, 2.0, 1.0));
+        assert_roots(&[-3.0, 2.0], solve_quadratic(1e-14, 1e-14, -6e-14));
+    }
+
+    #[test]
+    fn test_solve_cubic() {
+        // (x - 1)(x - 2)(x + 3)
+        assert_roots(&[-3.0, 1.0, 2.0], solve_cubic(2.0, 0.0, -14.0, 12.0));
+        // (x - 1)(x^2 + 1)
+        assert_roots(&[1.0], solve_cubic(1.0, -1.0, 1.0, -1.0));
+    }
+
+    #[test]
+    fn test_solve_quartic() {
+        // (x + 2)(x + 1)(x - 0.5)(x - 3)
+        assert_roots(
+            &[-2.0, -1.0, 0.5, 3.0],
+            solve_quartic(1.0, -0.5, -7.0, -2.5, 3.0),
+        );
+        // (x^2 - 4)(x^2 + 1)
+        assert_roots(&[-2.0, 2.0], solve_quartic(3.0, 0.0, -9.0, 0.0, -12.0));
+        assert_roots(&[], solve_quartic(1.0, 0.0, 1.0, 0.0, 1.0));
+        assert_roots(
+            &[-2.0, -1.0, 0.5, 3.0],
+            solve_quartic(1e-14, -0.5e-14, -7e-14, -2.5e-14, 3e-14),
+        );
+    }
+}
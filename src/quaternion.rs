@@ -0,0 +1,202 @@
+use std::ops;
+
+use crate::{matrix::Matrix4, vec3::Vec3};
+
+/// Unit quaternion representing a rotation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quaternion {
+    w: f64,
+    v: Vec3,
+}
+
+impl Quaternion {
+    pub fn identity() -> Quaternion {
+        Quaternion {
+            w: 1.0,
+            v: Vec3::default(),
+        }
+    }
+
+    /// Rotation by `theta` radians around `axis`, in the same direction as `Matrix4::rotation`.
+    pub fn from_axis_angle(axis: Vec3, theta: f64) -> Quaternion {
+        let (s, c) = (theta / 2.0).sin_cos();
+        Quaternion {
+            w: c,
+            v: axis.unit() * s,
+        }
+    }
+
+    /// Rotation around the x, y and z axes in this order, with angles in radians.
+    pub fn from_euler(x: f64, y: f64, z: f64) -> Quaternion {
+        Quaternion::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), z)
+            * Quaternion::from_axis_angle(Vec3::new(0.0, 1.0, 0.0), y)
+            * Quaternion::from_axis_angle(Vec3::new(1.0, 0.0, 0.0), x)
+    }
+
+    pub fn dot(self, rhs: Quaternion) -> f64 {
+        self.w * rhs.w + self.v.dot(rhs.v)
+    }
+
+    /// Angle in radians of the rotation from `self` to `other` along the shortest path.
+    pub fn angle_to(self, other: Quaternion) -> f64 {
+        2.0 * self.dot(other).abs().min(1.0).acos()
+    }
+
+    /// Spherical linear interpolation along the shortest path, `self` at `t = 0`.
+    pub fn slerp(self, other: Quaternion, t: f64) -> Quaternion {
+        let mut dot = self.dot(other);
+        // `q` and `-q` are the same rotation
+        let other = if dot < 0.0 {
+            dot = -dot;
+            -other
+        } else {
+            other
+        };
+        if dot > 0.9995 {
+            // Nearly parallel, where lerp is accurate and slerp divides by almost zero
+            return (self * (1.0 - t) + other * t).normalize();
+        }
+        let theta = dot.acos();
+        let sin_theta = theta.sin();
+        let a = ((1.0 - t) * theta).sin() / sin_theta;
+        let b = (t * theta).sin() / sin_theta;
+        self * a + other * b
+    }
+
+    pub fn to_matrix(self) -> Matrix4 {
+        let Quaternion { w, v } = self;
+        let (x, y, z) = (v.x(), v.y(), v.z());
+        Matrix4::new([
+            [
+                1.0 - 2.0 * (y * y + z * z),
+                2.0 * (x * y - w * z),
+                2.0 * (x * z + w * y),
+                0.0,
+            ],
+            [
+                2.0 * (x * y + w * z),
+                1.0 - 2.0 * (x * x + z * z),
+                2.0 * (y * z - w * x),
+                0.0,
+            ],
+            [
+                2.0 * (x * z - w * y),
+                2.0 * (y * z + w * x),
+                1.0 - 2.0 * (x * x + y * y),
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    fn normalize(self) -> Quaternion {
+        self * (1.0 / self.dot(self).sqrt())
+    }
+}
+
+impl Default for Quaternion {
+    fn default() -> Quaternion {
+        Quaternion::identity()
+    }
+}
+
+/// `a * b` applies `b` first, then `a`.
+impl ops::Mul for Quaternion {
+    type Output = Quaternion;
+
+    fn mul(self, rhs: Quaternion) -> Quaternion {
+        Quaternion {
+            w: self.w * rhs.w - self.v.dot(rhs.v),
+            v: rhs.v * self.w + self.v * rhs.w + self.v.cross(rhs.v),
+        }
+    }
+}
+
+impl ops::Mul<f64> for Quaternion {
+    type Output = Quaternion;
+
+    fn mul(self, rhs: f64) -> Quaternion {
+        Quaternion {
+            w: self.w * rhs,
+            v: self.v * rhs,
+        }
+    }
+}
+
+impl ops::Add for Quaternion {
+    type Output = Quaternion;
+
+    fn add(self, rhs: Quaternion) -> Quaternion {
+        Quaternion {
+            w: self.w + rhs.w,
+            v: self.v + rhs.v,
+        }
+    }
+}
+
+impl ops::Neg for Quaternion {
+    type Output = Quaternion;
+
+    fn neg(self) -> Quaternion {
+        self * -1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::vec3::Point3;
+
+    use super::*;
+
+    fn assert_near(expected: Matrix4, actual: Matrix4) {
+        for i in 0..4 {
+            for j in 0..4 {
+                assert!(
+                    (expected[i][j] - actual[i][j]).abs() < 1e-10,
+                    "expected {expected:?}, found {actual:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_to_matrix() {
+        let axis = Vec3::new(1.0, 2.0, -0.5);
+        assert_near(
+            Matrix4::rotation(axis, 0.8),
+            Quaternion::from_axis_angle(axis, 0.8).to_matrix(),
+        );
+        assert_near(
+            Matrix4::rotation(Vec3::new(0.0, 0.0, 1.0), 0.3)
+                * Matrix4::rotation(Vec3::new(0.0, 1.0, 0.0), 0.2)
+                * Matrix4::rotation(Vec3::new(1.0, 0.0, 0.0), 0.1),
+            Quaternion::from_euler(0.1, 0.2, 0.3).to_matrix(),
+        );
+    }
+
+    #[test]
+    fn test_slerp() {
+        let axis = Vec3::new(0.0, 1.0, 0.0);
+        let q0 = Quaternion::from_axis_angle(axis, 0.0);
+        let q1 = Quaternion::from_axis_angle(axis, PI / 2.0);
+
+        assert_near(
+            Matrix4::rotation(axis, PI / 8.0),
+            q0.slerp(q1, 0.25).to_matrix(),
+        );
+        assert!((q0.angle_to(q1) - PI / 2.0).abs() < 1e-10);
+
+        // The shortest path is taken even if the quaternions are in opposite hemispheres
+        let q2 = -Quaternion::from_axis_angle(axis, 0.2);
+        let p = q0
+            .slerp(q2, 0.5)
+            .to_matrix()
+            .transform_point(Point3::new(1.0, 0.0, 0.0));
+        assert!(
+            (p - Matrix4::rotation(axis, 0.1).transform_point(Point3::new(1.0, 0.0, 0.0))).length()
+                < 1e-10
+        );
+    }
+}
@@ -0,0 +1,60 @@
+use std::cell::RefCell;
+
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+thread_local! {
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+/// Handle to the random number generator of the current thread.
+///
+/// Unlike `rand::thread_rng()`, the generator can be reseeded with `seed` to make renders reproducible.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LocalRng;
+
+pub fn rng() -> LocalRng {
+    LocalRng
+}
+
+/// Reseeds the random number generator of the current thread.
+pub fn seed(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+impl RngCore for LocalRng {
+    fn next_u32(&mut self) -> u32 {
+        RNG.with(|rng| rng.borrow_mut().next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        RNG.with(|rng| rng.borrow_mut().next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        RNG.with(|rng| rng.borrow_mut().fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        RNG.with(|rng| rng.borrow_mut().try_fill_bytes(dest))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+
+    #[test]
+    fn test_seed_makes_sequence_reproducible() {
+        seed(42);
+        let a: [f64; 4] = rng().gen();
+        seed(42);
+        let b: [f64; 4] = rng().gen();
+        seed(43);
+        let c: [f64; 4] = rng().gen();
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+}
@@ -0,0 +1,566 @@
+use rand::Rng;
+#[cfg(feature = "parallel")]
+use rayon::prelude::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+
+use crate::{
+    camera::Camera,
+    hittables::{Hit, Hittable},
+    lights::{LightList, LightSample},
+    random,
+    ray::Ray,
+    scene::{Integrator, RenderSettings},
+    vec3::Color,
+};
+
+/// Renders the image, returning rows of linear radiance from the top.
+///
+/// `lights` are only used by integrators that sample them, see [`Integrator`].
+pub fn render(
+    world: &impl Hittable,
+    lights: &LightList,
+    camera: &Camera,
+    background: Color,
+    settings: &RenderSettings,
+) -> Vec<Vec<Color>> {
+    render_with_progress(world, lights, camera, background, settings, |_| {})
+}
+
+/// Same as `render`, except that `progress` is called with the number of scanlines remaining
+/// before each one is rendered.
+pub fn render_with_progress(
+    world: &impl Hittable,
+    lights: &LightList,
+    camera: &Camera,
+    background: Color,
+    settings: &RenderSettings,
+    mut progress: impl FnMut(usize),
+) -> Vec<Vec<Color>> {
+    let RenderSettings {
+        image_width,
+        image_height,
+        samples_per_pixel,
+        ..
+    } = *settings;
+    let mut image = Vec::<Vec<Color>>::with_capacity(image_height);
+    let sample = |i: usize, j: usize, s: i32| -> Color {
+        if let Some(seed) = settings.seed {
+            // Give every sample its own sequence so that the result does not depend on scheduling
+            let index = (j * image_width + i) as u64 * samples_per_pixel as u64 + s as u64;
+            random::seed(seed ^ index.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        }
+        let mut rng = random::rng();
+        let z: f64 = rng.gen();
+        let w: f64 = rng.gen();
+        let u = (i as f64 + z) / ((image_width - 1) as f64);
+        let v = (j as f64 + w) / ((image_height - 1) as f64);
+        let ray = camera.ray(u, v);
+        let RenderSettings {
+            max_depth,
+            roulette_depth,
+            ..
+        } = *settings;
+        match settings.integrator {
+            Integrator::Naive => ray_color(&ray, background, world, max_depth, roulette_depth),
+            Integrator::NextEvent => {
+                ray_color_next_event(&ray, background, world, lights, max_depth, roulette_depth)
+            }
+            Integrator::Mis => {
+                ray_color_mis(&ray, background, world, lights, max_depth, roulette_depth)
+            }
+        }
+    };
+
+    for j in (0..image_height).rev() {
+        progress(j);
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "parallel")] {
+                let mut row = Vec::<Color>::with_capacity(image_width);
+                (0..image_width)
+                    .into_par_iter()
+                    .map(|i| {
+                        let c: Color = (0..samples_per_pixel)
+                            .into_par_iter()
+                            .map(|s| sample(i, j, s))
+                            .sum();
+                        c / samples_per_pixel as f64
+                    })
+                    .collect_into_vec(&mut row);
+            } else {
+                let row = (0..image_width)
+                    .map(|i| {
+                        let c: Color = (0..samples_per_pixel)
+                            .map(|s| sample(i, j, s))
+                            .sum();
+                        c / samples_per_pixel as f64
+                    })
+                    .collect::<Vec<_>>();
+            }
+        }
+
+        image.push(row);
+    }
+    image
+}
+
+/// Radiance along the ray, following up to `max_depth` bounces.
+///
+/// After `roulette_depth` bounces, paths are ended at random in proportion to how little light
+/// they still carry (Russian roulette), and the others carry more to make up for them.
+pub fn ray_color(
+    ray: &Ray,
+    background: Color,
+    world: &impl Hittable,
+    max_depth: i32,
+    roulette_depth: i32,
+) -> Color {
+    let mut ray = *ray;
+    let mut color = Color::default();
+    let mut throughput = Color::new(1.0, 1.0, 1.0);
+    for bounce in 0..max_depth {
+        let Some(hit) = world.hit(&ray, 0.001, f64::INFINITY) else {
+            return color + throughput * background;
+        };
+        color += throughput * hit.material.emitted(hit.u, hit.v, &hit.p);
+        let Some(scatter) = hit.material.scatter(&ray, &hit) else {
+            break;
+        };
+
+        throughput = throughput * scatter.attenuation;
+        if !survives(&mut throughput, bounce + 1, roulette_depth) {
+            break;
+        }
+        ray = scatter.ray;
+    }
+    color
+}
+
+/// Radiance along the ray like `ray_color`, adding the light of a direction sampled toward
+/// `lights` at every bounce that is not specular (next event estimation).
+///
+/// Light from the directions that `lights` can sample is only gathered that way, so that it is not
+/// counted twice when a bounce happens to reach a light as well.
+pub fn ray_color_next_event(
+    ray: &Ray,
+    background: Color,
+    world: &impl Hittable,
+    lights: &LightList,
+    max_depth: i32,
+    roulette_depth: i32,
+) -> Color {
+    trace(
+        ray,
+        background,
+        world,
+        lights,
+        max_depth,
+        roulette_depth,
+        Weighting::Lights,
+    )
+}
+
+/// Radiance along the ray combining, at every bounce that is not specular, a direction sampled
+/// toward `lights` and the direction sampled by the material (multiple importance sampling).
+///
+/// The light reached by either direction is weighted by the power heuristic, so that each
+/// strategy counts most where it has the lower variance: sampling the lights for small lights,
+/// and sampling the material for glossy reflections of large ones.
+pub fn ray_color_mis(
+    ray: &Ray,
+    background: Color,
+    world: &impl Hittable,
+    lights: &LightList,
+    max_depth: i32,
+    roulette_depth: i32,
+) -> Color {
+    trace(
+        ray,
+        background,
+        world,
+        lights,
+        max_depth,
+        roulette_depth,
+        Weighting::PowerHeuristic,
+    )
+}
+
+/// How the light found by sampling the lights and by following the bounces are weighted against
+/// each other.
+#[derive(Debug, Clone, Copy)]
+enum Weighting {
+    /// The lights are only found by sampling them
+    Lights,
+    PowerHeuristic,
+}
+
+impl Weighting {
+    /// Weight of the light reached by a direction sampled toward the lights.
+    fn light(self, light_pdf: f64, scattering_pdf: f64) -> f64 {
+        match self {
+            Weighting::Lights => 1.0,
+            Weighting::PowerHeuristic => power_heuristic(light_pdf, scattering_pdf),
+        }
+    }
+
+    /// Weight of the light reached by a direction sampled by the material.
+    fn bounce(self, scattering_pdf: f64, light_pdf: f64) -> f64 {
+        match self {
+            Weighting::Lights if light_pdf > 0.0 => 0.0,
+            Weighting::Lights => 1.0,
+            Weighting::PowerHeuristic => power_heuristic(scattering_pdf, light_pdf),
+        }
+    }
+}
+
+/// Weight of a sample taken with the density `pdf`, against another strategy with `other_pdf`.
+fn power_heuristic(pdf: f64, other_pdf: f64) -> f64 {
+    let (a, b) = (pdf * pdf, other_pdf * other_pdf);
+    if a + b > 0.0 {
+        a / (a + b)
+    } else {
+        0.0
+    }
+}
+
+fn trace(
+    ray: &Ray,
+    background: Color,
+    world: &impl Hittable,
+    lights: &LightList,
+    max_depth: i32,
+    roulette_depth: i32,
+    weighting: Weighting,
+) -> Color {
+    let mut ray = *ray;
+    let mut color = Color::default();
+    let mut throughput = Color::new(1.0, 1.0, 1.0);
+    // Density with which the material sampled the direction of `ray`, or `None` for the camera ray
+    // and specular bounces, whose light is counted in full
+    let mut scattering_pdf = None;
+    for bounce in 0..max_depth {
+        let Some(hit) = world.hit(&ray, 0.001, f64::INFINITY) else {
+            return color + throughput * background;
+        };
+        let mut emitted = hit.material.emitted(hit.u, hit.v, &hit.p);
+        // The density of the lights is only looked up where there is light to weight
+        if let Some(pdf) = scattering_pdf.filter(|_| emitted != Color::default()) {
+            emitted *= weighting.bounce(pdf, lights.pdf_value(ray.origin, ray.direction));
+        }
+        color += throughput * emitted;
+        let Some(scatter) = hit.material.scatter(&ray, &hit) else {
+            break;
+        };
+
+        if scatter.pdf.is_some() && bounce + 1 < max_depth {
+            // The light reached by the sampled direction is one bounce further
+            color += throughput
+                * sample_light(&ray, &hit, scatter.attenuation, world, lights, weighting);
+        }
+        scattering_pdf = scatter.pdf;
+        throughput = throughput * scatter.attenuation;
+        if !survives(&mut throughput, bounce + 1, roulette_depth) {
+            break;
+        }
+        ray = scatter.ray;
+    }
+    color
+}
+
+/// Decides whether a path goes on after `bounces` bounces, with Russian roulette once there are
+/// at least `roulette_depth` of them.
+///
+/// The path survives with the probability of its brightest channel, and its throughput is divided
+/// by that probability to keep the estimate unbiased.
+fn survives(throughput: &mut Color, bounces: i32, roulette_depth: i32) -> bool {
+    if bounces < roulette_depth {
+        return true;
+    }
+    let probability = throughput
+        .x()
+        .max(throughput.y())
+        .max(throughput.z())
+        .min(1.0);
+    if random::rng().gen::<f64>() >= probability {
+        return false;
+    }
+    *throughput /= probability;
+    true
+}
+
+/// Light arriving at the hit from a direction sampled toward `lights`, scattered along `ray`.
+///
+/// Lights at a single point or in a single direction only need a shadow ray to check that nothing
+/// lies in between.
+fn sample_light(
+    ray: &Ray,
+    hit: &Hit,
+    attenuation: Color,
+    world: &impl Hittable,
+    lights: &LightList,
+    weighting: Weighting,
+) -> Color {
+    match lights.sample(hit.p) {
+        None => Color::default(),
+        Some(LightSample::Area { direction, pdf }) => {
+            let light_ray = Ray::new(hit.p, direction, ray.time);
+            let scattering_pdf = hit.material.scattering_pdf(ray, hit, &light_ray);
+            if scattering_pdf <= 0.0 {
+                return Color::default();
+            }
+            let Some(light_hit) = world.hit(&light_ray, 0.001, f64::INFINITY) else {
+                return Color::default();
+            };
+            let emitted = light_hit
+                .material
+                .emitted(light_hit.u, light_hit.v, &light_hit.p);
+            attenuation * emitted * scattering_pdf / pdf * weighting.light(pdf, scattering_pdf)
+        }
+        Some(LightSample::Delta {
+            direction,
+            distance,
+            irradiance,
+        }) => {
+            let light_ray = Ray::new(hit.p, direction, ray.time);
+            let scattering_pdf = hit.material.scattering_pdf(ray, hit, &light_ray);
+            if scattering_pdf <= 0.0 || world.hit(&light_ray, 0.001, distance).is_some() {
+                return Color::default();
+            }
+            // No bounce can reach the light, so nothing is weighted against it
+            attenuation * irradiance * scattering_pdf
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        f64::consts::PI,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use crate::{
+        hittables::{Aabb, Box as HittableBox, HittableVec, XzRect},
+        lights::{AreaLight, DirectionalLight, PointLight},
+        materials::{DiffuseLight, Lambertian, Metal},
+        textures::SolidColor,
+        vec3::{Point3, Vec3},
+    };
+
+    use super::*;
+
+    fn light() -> XzRect<DiffuseLight<SolidColor>> {
+        XzRect::new(
+            -1.0,
+            1.0,
+            -1.0,
+            1.0,
+            2.0,
+            DiffuseLight::new_with_color(Color::new(4.0, 4.0, 4.0)),
+        )
+    }
+
+    /// A floor under a square light, and the exact radiance of its point under the center of
+    /// the light, from the form factor of a parallel rectangle
+    fn scene() -> (HittableVec, LightList, f64) {
+        let light = light();
+        let floor = XzRect::new(
+            -100.0,
+            100.0,
+            -100.0,
+            100.0,
+            0.0,
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        );
+        let world: HittableVec = vec![Box::new(light.clone()), Box::new(floor)];
+        let mut lights = LightList::new();
+        lights.push(AreaLight::new(light, Color::new(4.0, 4.0, 4.0)));
+
+        // Each quarter of the light spans 0.5 of the height along x and z
+        let x = 0.5f64;
+        let quarter =
+            2.0 * x / (1.0 + x * x).sqrt() * (x / (1.0 + x * x).sqrt()).atan() / (2.0 * PI);
+        (world, lights, 0.5 * 4.0 * 4.0 * quarter)
+    }
+
+    fn mean(n: usize, mut sample: impl FnMut() -> Color) -> f64 {
+        (0..n).map(|_| sample().x()).sum::<f64>() / n as f64
+    }
+
+    #[test]
+    fn test_next_event_converges_to_naive() {
+        let (world, lights, expected) = scene();
+        let ray = Ray::new(Point3::new(0.0, 1.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        random::seed(1);
+
+        let naive = mean(200_000, || {
+            ray_color(&ray, Color::default(), &world, 10, 10)
+        });
+        let next_event = mean(10_000, || {
+            ray_color_next_event(&ray, Color::default(), &world, &lights, 10, 10)
+        });
+        assert!((naive - expected).abs() < 0.01 * expected);
+        assert!((next_event - expected).abs() < 0.01 * expected);
+
+        // Without lights to sample, it falls back to the bounces
+        let no_lights = LightList::new();
+        let fallback = mean(200_000, || {
+            ray_color_next_event(&ray, Color::default(), &world, &no_lights, 10, 10)
+        });
+        assert!((fallback - expected).abs() < 0.01 * expected);
+    }
+
+    #[test]
+    fn test_mis_converges_to_naive() {
+        let (world, lights, expected) = scene();
+        let ray = Ray::new(Point3::new(0.0, 1.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        random::seed(1);
+
+        let mis = mean(10_000, || {
+            ray_color_mis(&ray, Color::default(), &world, &lights, 10, 10)
+        });
+        assert!((mis - expected).abs() < 0.01 * expected);
+
+        // A glossy floor reflecting the edge of the light
+        let light = light();
+        let floor = XzRect::new(
+            -100.0,
+            100.0,
+            -100.0,
+            100.0,
+            0.0,
+            Metal::new(Color::new(0.8, 0.8, 0.8), 0.3),
+        );
+        let world: HittableVec = vec![Box::new(light.clone()), Box::new(floor)];
+        let mut lights = LightList::new();
+        lights.push(AreaLight::new(light, Color::new(4.0, 4.0, 4.0)));
+        let ray = Ray::new(Point3::new(-0.5, 1.0, 0.0), Vec3::new(0.5, -1.0, 0.0), 0.0);
+
+        let naive = mean(400_000, || {
+            ray_color(&ray, Color::default(), &world, 10, 10)
+        });
+        let next_event = mean(400_000, || {
+            ray_color_next_event(&ray, Color::default(), &world, &lights, 10, 10)
+        });
+        let mis = mean(100_000, || {
+            ray_color_mis(&ray, Color::default(), &world, &lights, 10, 10)
+        });
+        assert!((next_event - naive).abs() < 0.02 * naive);
+        assert!((mis - naive).abs() < 0.02 * naive);
+    }
+
+    #[test]
+    fn test_russian_roulette_is_unbiased() {
+        /// Counts the rays traced against a hittable
+        struct Counted<H: Hittable> {
+            hittable: H,
+            rays: AtomicUsize,
+        }
+
+        impl<H: Hittable> Hittable for Counted<H> {
+            fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit<'_>> {
+                self.rays.fetch_add(1, Ordering::Relaxed);
+                self.hittable.hit(ray, t_min, t_max)
+            }
+
+            fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+                self.hittable.bounding_box(time0, time1)
+            }
+        }
+
+        // A closed room, where paths only end on the light or at the maximum depth
+        let light = XzRect::new(
+            -0.5,
+            0.5,
+            -0.5,
+            0.5,
+            0.999,
+            DiffuseLight::new_with_color(Color::new(4.0, 4.0, 4.0)),
+        );
+        let room = HittableBox::new(
+            Point3::new(-1.0, -1.0, -1.0),
+            Point3::new(1.0, 1.0, 1.0),
+            Lambertian::new_with_color(Color::new(0.8, 0.8, 0.8)),
+        );
+        let world = Counted {
+            hittable: vec![Box::new(light.clone()) as Box<dyn Hittable>, Box::new(room)],
+            rays: AtomicUsize::new(0),
+        };
+        let mut lights = LightList::new();
+        lights.push(AreaLight::new(light, Color::new(4.0, 4.0, 4.0)));
+        let ray = Ray::new(Point3::default(), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        random::seed(1);
+
+        let n = 200_000;
+        let full = mean(n, || ray_color(&ray, Color::default(), &world, 50, 50));
+        let full_rays = world.rays.swap(0, Ordering::Relaxed);
+        let roulette = mean(n, || ray_color(&ray, Color::default(), &world, 50, 2));
+        let roulette_rays = world.rays.swap(0, Ordering::Relaxed);
+        assert!((roulette - full).abs() < 0.03 * full);
+        assert!(roulette_rays < full_rays);
+
+        let mis = mean(n / 4, || {
+            ray_color_mis(&ray, Color::default(), &world, &lights, 50, 2)
+        });
+        assert!((mis - full).abs() < 0.03 * full);
+
+        // The maximum depth still bounds the paths
+        world.rays.store(0, Ordering::Relaxed);
+        ray_color(&ray, Color::default(), &world, 3, 0);
+        assert!(world.rays.load(Ordering::Relaxed) <= 3);
+    }
+
+    #[test]
+    fn test_delta_lights() {
+        let floor = || {
+            XzRect::new(
+                -100.0,
+                100.0,
+                -100.0,
+                100.0,
+                0.0,
+                Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+            )
+        };
+        let world: HittableVec = vec![Box::new(floor())];
+        let ray = Ray::new(Point3::new(0.0, 1.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        random::seed(1);
+
+        let mut point = LightList::new();
+        point.push(PointLight::new(
+            Point3::new(0.0, 2.0, 0.0),
+            Color::new(8.0, 8.0, 8.0),
+        ));
+        let mut directional = LightList::new();
+        directional.push(DirectionalLight::new(
+            Vec3::new(0.0, -1.0, 0.0),
+            Color::new(2.0, 2.0, 2.0),
+            10.0,
+        ));
+
+        // Only the first bounce is lit, since the others go to the black sky
+        for lights in [&point, &directional] {
+            let next_event = ray_color_next_event(&ray, Color::default(), &world, lights, 5, 5);
+            let mis = ray_color_mis(&ray, Color::default(), &world, lights, 5, 5);
+            assert!((next_event.x() - 1.0 / PI).abs() < 1e-12);
+            assert!((mis.x() - 1.0 / PI).abs() < 1e-12);
+        }
+        assert_eq!(
+            Color::default(),
+            ray_color(&ray, Color::default(), &world, 5, 5)
+        );
+
+        // In the shadow of a roof, whose underside is only lit by the floor beyond it
+        let roof = XzRect::new(
+            -1.0,
+            1.0,
+            -1.0,
+            1.0,
+            1.5,
+            Lambertian::new_with_color(Color::new(0.5, 0.5, 0.5)),
+        );
+        let world: HittableVec = vec![Box::new(floor()), Box::new(roof)];
+        for lights in [&point, &directional] {
+            let shadow = ray_color_next_event(&ray, Color::default(), &world, lights, 2, 2);
+            assert_eq!(Color::default(), shadow);
+        }
+    }
+}
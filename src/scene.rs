@@ -0,0 +1,117 @@
+mod description;
+
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result};
+
+use crate::{
+    camera::Camera,
+    hittables::HittableVec,
+    lights::LightList,
+    vec3::{Color, Point3, Vec3},
+};
+
+/// Everything needed to render an image: the world, the camera and the render settings.
+pub struct Scene {
+    pub world: HittableVec,
+    /// Lights that integrators sample directly: the emissive objects of the world again, and
+    /// lights at a single point or in a single direction, which only they can find
+    pub lights: LightList,
+    pub background: Color,
+    pub camera: CameraSettings,
+    pub render: RenderSettings,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraSettings {
+    pub look_from: Point3,
+    pub look_at: Point3,
+    pub v_up: Vec3,
+    /// Vertical field-of-view in radians
+    pub theta: f64,
+    pub aperture: f64,
+    pub focus_dist: f64,
+    pub time0: f64,
+    pub time1: f64,
+}
+
+/// Largest image width or height accepted from a scene file or the command line
+pub const MAX_IMAGE_SIZE: usize = 1 << 16;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderSettings {
+    pub image_width: usize,
+    pub image_height: usize,
+    pub samples_per_pixel: i32,
+    /// Hard limit on the number of bounces of a ray
+    pub max_depth: i32,
+    /// Number of bounces after which dim paths are ended at random, see `ray_color`
+    pub roulette_depth: i32,
+    /// Seed for reproducible renders; the output differs on every run if `None`
+    pub seed: Option<u64>,
+    pub integrator: Integrator,
+}
+
+/// How the radiance along each camera ray is estimated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Integrator {
+    /// Follows random bounces until they happen to reach a light, so that it misses lights at a
+    /// single point or in a single direction
+    #[default]
+    Naive,
+    /// Also samples a direction toward the lights of the scene at every bounce that is not specular
+    NextEvent,
+    /// Like `NextEvent`, weighting the light sampled toward the lights and the light reached by
+    /// the bounces by how likely each strategy is to find it
+    Mis,
+}
+
+impl Scene {
+    /// Loads a scene from a TOML scene description file.
+    ///
+    /// Relative paths in the file are resolved against the directory of the file.
+    pub fn new_with_filename(filename: impl AsRef<Path>) -> Result<Scene> {
+        let filename = filename.as_ref();
+        let source = fs::read_to_string(filename)
+            .with_context(|| format!("failed to read {}", filename.display()))?;
+        let base_dir = filename.parent().unwrap_or_else(|| Path::new(""));
+        description::parse(&source, base_dir)
+            .with_context(|| format!("failed to load {}", filename.display()))
+    }
+}
+
+impl CameraSettings {
+    pub fn build(&self, aspect_ratio: f64) -> Camera {
+        Camera::new(
+            self.look_from,
+            self.look_at,
+            self.v_up,
+            self.theta,
+            aspect_ratio,
+            self.aperture,
+            self.focus_dist,
+            self.time0,
+            self.time1,
+        )
+    }
+}
+
+impl Default for RenderSettings {
+    fn default() -> RenderSettings {
+        RenderSettings {
+            image_width: 400,
+            image_height: 225,
+            samples_per_pixel: 100,
+            max_depth: 50,
+            roulette_depth: 5,
+            seed: None,
+            integrator: Integrator::default(),
+        }
+    }
+}
+
+impl RenderSettings {
+    pub fn aspect_ratio(&self) -> f64 {
+        self.image_width as f64 / self.image_height as f64
+    }
+}
@@ -0,0 +1,524 @@
+mod lights;
+mod materials;
+mod objects;
+mod sdf;
+mod transforms;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    f64::consts::PI,
+    path::Path,
+    sync::Arc,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Deserialize;
+
+use crate::{
+    hittables::{BvhTree, Hittable, HittableVec},
+    lights::{AreaLight, LightList},
+    materials::Material,
+    textures::Texture,
+    vec3::Vec3,
+};
+
+use self::{
+    lights::{light_of, LightDescription},
+    materials::{MaterialDescription, TextureDescription},
+    objects::ObjectDescription,
+};
+use super::{CameraSettings, Integrator, RenderSettings, Scene, MAX_IMAGE_SIZE};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneDescription {
+    camera: CameraDescription,
+    #[serde(default)]
+    render: RenderDescription,
+    #[serde(default)]
+    background: [f64; 3],
+    #[serde(default)]
+    textures: BTreeMap<String, TextureDescription>,
+    #[serde(default)]
+    materials: BTreeMap<String, MaterialDescription>,
+    /// Objects placed by `instance` objects, each of which is built only once
+    #[serde(default)]
+    models: BTreeMap<String, ObjectDescription>,
+    /// Emissive spheres, rectangles, quads and triangles among them, translated or not, are also
+    /// the lights of the scene
+    objects: Vec<ObjectDescription>,
+    /// Lights at a single point or in a single direction, besides the emissive objects
+    #[serde(default)]
+    lights: Vec<LightDescription>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CameraDescription {
+    look_from: [f64; 3],
+    look_at: [f64; 3],
+    #[serde(default = "default_v_up")]
+    v_up: [f64; 3],
+    /// Vertical field-of-view in degrees
+    vfov: f64,
+    #[serde(default)]
+    aperture: f64,
+    #[serde(default = "default_focus_dist")]
+    focus_dist: f64,
+    #[serde(default)]
+    time0: f64,
+    #[serde(default = "default_time1")]
+    time1: f64,
+}
+
+fn default_v_up() -> [f64; 3] {
+    [0.0, 1.0, 0.0]
+}
+
+fn default_focus_dist() -> f64 {
+    10.0
+}
+
+fn default_time1() -> f64 {
+    1.0
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+struct RenderDescription {
+    image_width: usize,
+    aspect_ratio: f64,
+    samples_per_pixel: i32,
+    max_depth: i32,
+    roulette_depth: i32,
+    integrator: IntegratorDescription,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum IntegratorDescription {
+    #[default]
+    Naive,
+    NextEvent,
+    Mis,
+}
+
+impl Default for RenderDescription {
+    fn default() -> RenderDescription {
+        let settings = RenderSettings::default();
+        RenderDescription {
+            image_width: settings.image_width,
+            aspect_ratio: settings.aspect_ratio(),
+            samples_per_pixel: settings.samples_per_pixel,
+            max_depth: settings.max_depth,
+            roulette_depth: settings.roulette_depth,
+            integrator: IntegratorDescription::default(),
+        }
+    }
+}
+
+pub(super) fn parse(source: &str, base_dir: &Path) -> Result<Scene> {
+    let description: SceneDescription = toml::from_str(source)?;
+    SceneBuilder::new(&description, base_dir).build()
+}
+
+fn vec3([x, y, z]: [f64; 3]) -> Vec3 {
+    Vec3::new(x, y, z)
+}
+
+/// Turns named descriptions into shared instances, creating each of them only once.
+struct SceneBuilder<'a> {
+    description: &'a SceneDescription,
+    base_dir: &'a Path,
+    textures: HashMap<&'a str, Arc<dyn Texture>>,
+    materials: HashMap<&'a str, Arc<dyn Material>>,
+    models: HashMap<&'a str, Arc<BvhTree>>,
+    /// Names of textures being built, to detect cyclic references
+    pending_textures: Vec<&'a str>,
+    /// Names of models being built, to detect cyclic references
+    pending_models: Vec<&'a str>,
+}
+
+impl<'a> SceneBuilder<'a> {
+    fn new(description: &'a SceneDescription, base_dir: &'a Path) -> SceneBuilder<'a> {
+        SceneBuilder {
+            description,
+            base_dir,
+            textures: HashMap::new(),
+            materials: HashMap::new(),
+            models: HashMap::new(),
+            pending_textures: vec![],
+            pending_models: vec![],
+        }
+    }
+
+    fn build(mut self) -> Result<Scene> {
+        let description = self.description;
+
+        let camera = &description.camera;
+        if camera.vfov <= 0.0 || camera.vfov >= 180.0 {
+            bail!("camera.vfov: must be in (0, 180), found {}", camera.vfov);
+        }
+        if camera.look_from == camera.look_at {
+            bail!("camera: look_from and look_at must differ");
+        }
+        let render = &description.render;
+        if !(2..=MAX_IMAGE_SIZE).contains(&render.image_width) {
+            bail!(
+                "render.image_width: must be in [2, {MAX_IMAGE_SIZE}], found {}",
+                render.image_width
+            );
+        }
+        if !(render.aspect_ratio.is_finite() && render.aspect_ratio > 0.0) {
+            bail!(
+                "render.aspect_ratio: must be positive and finite, found {}",
+                render.aspect_ratio
+            );
+        }
+        let image_height = ((render.image_width as f64) / render.aspect_ratio) as usize;
+        if !(2..=MAX_IMAGE_SIZE).contains(&image_height) {
+            bail!(
+                "render.aspect_ratio: the image must be 2 to {MAX_IMAGE_SIZE} pixels high, found {image_height}"
+            );
+        }
+        if render.samples_per_pixel < 1 {
+            bail!("render.samples_per_pixel: must be at least 1");
+        }
+        if render.max_depth < 1 {
+            bail!("render.max_depth: must be at least 1");
+        }
+        if render.roulette_depth < 0 {
+            bail!("render.roulette_depth: must not be negative");
+        }
+
+        // Build every named texture and material so that unused ones are validated too
+        for name in description.textures.keys() {
+            self.texture(name)
+                .with_context(|| format!("textures.{name}"))?;
+        }
+        for name in description.materials.keys() {
+            self.material(name)
+                .with_context(|| format!("materials.{name}"))?;
+        }
+        for name in description.models.keys() {
+            self.model(name).with_context(|| format!("models.{name}"))?;
+        }
+
+        if description.objects.is_empty() {
+            bail!("objects: at least one object is required");
+        }
+        let mut world = HittableVec::new();
+        let mut lights = LightList::new();
+        for (i, object) in description.objects.iter().enumerate() {
+            let context = || format!("objects[{i}]");
+            let hittable = self.object(object).with_context(context)?;
+            match self.emission(object).with_context(context)? {
+                Some(radiance) => {
+                    // Shared by the world and the lights
+                    let hittable = Arc::<dyn Hittable>::from(hittable);
+                    lights.push(AreaLight::new(hittable.clone(), radiance));
+                    world.push(Box::new(hittable));
+                }
+                None => world.push(hittable),
+            }
+        }
+        // Directional lights shine on all of the world, which sets their power
+        let radius = world
+            .bounding_box(camera.time0, camera.time1)
+            .map_or(1.0, |aabb| (aabb.max - aabb.min).length() / 2.0);
+        for (i, light) in description.lights.iter().enumerate() {
+            lights.push(light_of(light, radius).with_context(|| format!("lights[{i}]"))?);
+        }
+
+        Ok(Scene {
+            world,
+            lights,
+            background: vec3(description.background),
+            camera: CameraSettings {
+                look_from: vec3(camera.look_from),
+                look_at: vec3(camera.look_at),
+                v_up: vec3(camera.v_up),
+                theta: camera.vfov * PI / 180.0,
+                aperture: camera.aperture,
+                focus_dist: camera.focus_dist,
+                time0: camera.time0,
+                time1: camera.time1,
+            },
+            render: RenderSettings {
+                image_width: render.image_width,
+                image_height,
+                samples_per_pixel: render.samples_per_pixel,
+                max_depth: render.max_depth,
+                roulette_depth: render.roulette_depth,
+                seed: None,
+                integrator: match render.integrator {
+                    IntegratorDescription::Naive => Integrator::Naive,
+                    IntegratorDescription::NextEvent => Integrator::NextEvent,
+                    IntegratorDescription::Mis => Integrator::Mis,
+                },
+            },
+        })
+    }
+}
+
+fn check_positive(name: &str, value: f64) -> Result<()> {
+    if value > 0.0 {
+        Ok(())
+    } else {
+        Err(anyhow!("{name}: must be positive, found {value}"))
+    }
+}
+
+fn check_range(axis: &str, min: f64, max: f64) -> Result<()> {
+    if min < max {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "{axis}: the lower bound {min} must be less than the upper bound {max}"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ray::Ray, vec3::Color};
+
+    use super::*;
+
+    pub(super) const CORNELL_BOX: &str = r#"
+background = [0.0, 0.0, 0.0]
+
+[camera]
+look_from = [278.0, 278.0, -800.0]
+look_at = [278.0, 278.0, 0.0]
+vfov = 40.0
+
+[render]
+image_width = 600
+aspect_ratio = 1.0
+samples_per_pixel = 200
+
+[textures.checker]
+type = "checker"
+odd = "green"
+even = "white"
+
+[textures.green]
+type = "solid"
+color = [0.12, 0.45, 0.15]
+
+[textures.white]
+type = "solid"
+color = [0.73, 0.73, 0.73]
+
+[materials.white]
+type = "lambertian"
+texture = "white"
+
+[materials.floor]
+type = "lambertian"
+texture = "checker"
+
+[materials.light]
+type = "diffuse_light"
+color = [15.0, 15.0, 15.0]
+
+[[objects]]
+type = "xz_rect"
+x0 = 213.0
+x1 = 343.0
+z0 = 227.0
+z1 = 332.0
+y = 554.0
+material = "light"
+
+[[objects]]
+type = "xz_rect"
+x0 = 0.0
+x1 = 555.0
+z0 = 0.0
+z1 = 555.0
+y = 0.0
+material = "floor"
+
+[[objects]]
+type = "constant_medium"
+density = 0.01
+color = [0.0, 0.0, 0.0]
+
+[objects.boundary]
+type = "translate"
+offset = [265.0, 0.0, 295.0]
+
+[objects.boundary.object]
+type = "rotate_y"
+angle = 15.0
+
+[objects.boundary.object.object]
+type = "box"
+min = [0.0, 0.0, 0.0]
+max = [165.0, 330.0, 165.0]
+material = "white"
+"#;
+
+    pub(super) fn parse_error(source: &str) -> String {
+        match parse(source, Path::new("")) {
+            Ok(_) => panic!("parse should fail"),
+            Err(error) => format!("{error:#}"),
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let scene = parse(CORNELL_BOX, Path::new("")).unwrap();
+
+        assert_eq!(3, scene.world.len());
+        assert_eq!(Color::new(0.0, 0.0, 0.0), scene.background);
+        assert_eq!(600, scene.render.image_width);
+        assert_eq!(600, scene.render.image_height);
+        assert_eq!(200, scene.render.samples_per_pixel);
+        assert_eq!(50, scene.render.max_depth);
+        assert_eq!(5, scene.render.roulette_depth);
+        assert_eq!(Integrator::Naive, scene.render.integrator);
+        assert_eq!(1, scene.lights.len());
+        assert_eq!(Vec3::new(0.0, 1.0, 0.0), scene.camera.v_up);
+        assert!((scene.camera.theta - 40.0 * PI / 180.0).abs() < 1e-12);
+
+        let ray = Ray::new(
+            Vec3::new(278.0, 278.0, 278.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            0.0,
+        );
+        let hit = scene.world.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert_eq!(
+            Color::new(15.0, 15.0, 15.0),
+            hit.material.emitted(hit.u, hit.v, &hit.p)
+        );
+    }
+
+    #[test]
+    fn test_parse_bundled_scene() {
+        let scene = parse(
+            include_str!("../../scenes/cornell_box.toml"),
+            Path::new("scenes"),
+        )
+        .unwrap();
+        assert_eq!(8, scene.world.len());
+        assert_eq!(1, scene.lights.len());
+        assert_eq!(Integrator::NextEvent, scene.render.integrator);
+    }
+
+    #[test]
+    fn test_unknown_key() {
+        let source = CORNELL_BOX.replace("vfov = 40.0", "vfov = 40.0\nfov = 40.0");
+        let error = parse_error(&source);
+        assert!(error.contains("unknown field `fov`"), "{error}");
+
+        let source = CORNELL_BOX.replace("density = 0.01", "density = 0.01\nopacity = 1.0");
+        let error = parse_error(&source);
+        assert!(error.contains("unknown field `opacity`"), "{error}");
+
+        let source = CORNELL_BOX.replace("type = \"box\"", "type = \"cube\"");
+        let error = parse_error(&source);
+        assert!(error.contains("unknown variant `cube`"), "{error}");
+    }
+
+    #[test]
+    fn test_invalid_value() {
+        let source = CORNELL_BOX.replace("image_width = 600", "image_width = \"wide\"");
+        let error = parse_error(&source);
+        assert!(error.contains("invalid type: string \"wide\""), "{error}");
+
+        let source = CORNELL_BOX.replace("x1 = 343.0", "x1 = 200.0");
+        assert_eq!(
+            "objects[0]: x: the lower bound 213 must be less than the upper bound 200",
+            parse_error(&source)
+        );
+
+        let source = CORNELL_BOX.replace("density = 0.01", "density = 0.0");
+        assert_eq!(
+            "objects[2]: density: must be positive, found 0",
+            parse_error(&source)
+        );
+
+        let source = CORNELL_BOX.replace("density = 0.01", "density = nan");
+        assert_eq!(
+            "objects[2]: density: must be positive, found NaN",
+            parse_error(&source)
+        );
+
+        for (aspect_ratio, error) in [
+            ("0.0", "must be positive and finite, found 0"),
+            ("nan", "must be positive and finite, found NaN"),
+            ("inf", "must be positive and finite, found inf"),
+            (
+                "1e-9",
+                "the image must be 2 to 65536 pixels high, found 600000000000",
+            ),
+        ] {
+            let source = CORNELL_BOX.replace(
+                "aspect_ratio = 1.0",
+                &format!("aspect_ratio = {aspect_ratio}"),
+            );
+            assert_eq!(
+                format!("render.aspect_ratio: {error}"),
+                parse_error(&source)
+            );
+        }
+
+        let source = CORNELL_BOX.replace("image_width = 600", "image_width = 100000");
+        assert_eq!(
+            "render.image_width: must be in [2, 65536], found 100000",
+            parse_error(&source)
+        );
+
+        let source = CORNELL_BOX.replace(
+            "samples_per_pixel = 200",
+            "samples_per_pixel = 200\nmax_depth = 0",
+        );
+        assert_eq!("render.max_depth: must be at least 1", parse_error(&source));
+
+        let source = CORNELL_BOX.replace(
+            "samples_per_pixel = 200",
+            "samples_per_pixel = 200\nroulette_depth = -1",
+        );
+        assert_eq!(
+            "render.roulette_depth: must not be negative",
+            parse_error(&source)
+        );
+
+        let source =
+            CORNELL_BOX.replace("max = [165.0, 330.0, 165.0]", "max = [165.0, 0.0, 165.0]");
+        assert_eq!(
+            "objects[2]: boundary: object: object: y: the lower bound 0 must be less than the upper bound 0",
+            parse_error(&source)
+        );
+    }
+
+    #[test]
+    fn test_unknown_reference() {
+        let source = CORNELL_BOX.replace("material = \"floor\"", "material = \"wall\"");
+        assert_eq!("objects[1]: unknown material `wall`", parse_error(&source));
+
+        let source = CORNELL_BOX.replace("even = \"white\"", "even = \"black\"");
+        assert_eq!(
+            "textures.checker: unknown texture `black`",
+            parse_error(&source)
+        );
+
+        let source = CORNELL_BOX.replace("even = \"white\"", "even = \"checker\"");
+        assert_eq!(
+            "textures.checker: cyclic reference to texture `checker`",
+            parse_error(&source)
+        );
+
+        let source = CORNELL_BOX.replace(
+            "texture = \"white\"",
+            "color = [1.0, 1.0, 1.0]\ntexture = \"white\"",
+        );
+        assert_eq!(
+            "materials.white: only one of `color` and `texture` can be given",
+            parse_error(&source)
+        );
+    }
+}
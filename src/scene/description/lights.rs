@@ -0,0 +1,301 @@
+use std::f64::consts::PI;
+
+use anyhow::{anyhow, bail, Result};
+use serde::Deserialize;
+
+use crate::{
+    lights::{DirectionalLight, Light, PointLight, SpotLight},
+    textures::Texture,
+    vec3::{Color, Point3},
+};
+
+use super::{materials::MaterialDescription, objects::ObjectDescription, vec3, SceneBuilder};
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub(super) enum LightDescription {
+    Point {
+        position: [f64; 3],
+        intensity: [f64; 3],
+    },
+    /// Cone of light around `direction`, fading between the angles in degrees from its axis
+    Spot {
+        position: [f64; 3],
+        direction: [f64; 3],
+        intensity: [f64; 3],
+        inner_angle: f64,
+        outer_angle: f64,
+    },
+    /// Light from infinitely far away, traveling along `direction`
+    Directional {
+        direction: [f64; 3],
+        irradiance: [f64; 3],
+    },
+}
+
+impl<'a> SceneBuilder<'a> {
+    /// Radiance of an object that can be sampled as a light and has an emissive material, on
+    /// average over its texture.
+    pub(super) fn emission(&mut self, description: &'a ObjectDescription) -> Result<Option<Color>> {
+        let material = match description {
+            ObjectDescription::Sphere { material, .. }
+            | ObjectDescription::Quad { material, .. }
+            | ObjectDescription::XyRect { material, .. }
+            | ObjectDescription::XzRect { material, .. }
+            | ObjectDescription::YzRect { material, .. }
+            | ObjectDescription::Triangle { material, .. } => material,
+            ObjectDescription::Translate { object, .. } => return self.emission(object),
+            _ => return Ok(None),
+        };
+        let Some(MaterialDescription::DiffuseLight { color, texture }) =
+            self.description.materials.get(material)
+        else {
+            return Ok(None);
+        };
+        let texture = self.color_or_texture(*color, texture)?;
+        Ok(Some(average(texture.as_ref())))
+    }
+}
+
+pub(super) fn light_of(description: &LightDescription, radius: f64) -> Result<Box<dyn Light>> {
+    Ok(match description {
+        LightDescription::Point {
+            position,
+            intensity,
+        } => {
+            check_finite("position", position)?;
+            check_finite("intensity", intensity)?;
+            Box::new(PointLight::new(vec3(*position), vec3(*intensity)))
+        }
+        LightDescription::Spot {
+            position,
+            direction,
+            intensity,
+            inner_angle,
+            outer_angle,
+        } => {
+            check_finite("position", position)?;
+            check_finite("direction", direction)?;
+            check_finite("intensity", intensity)?;
+            if vec3(*direction).is_near_zero() {
+                bail!("direction: must not be zero");
+            }
+            if !(*outer_angle > 0.0 && *outer_angle <= 180.0) {
+                bail!("outer_angle: must be in (0, 180], found {outer_angle}");
+            }
+            if !(*inner_angle >= 0.0 && inner_angle <= outer_angle) {
+                bail!("inner_angle: must be in [0, outer_angle], found {inner_angle}");
+            }
+            Box::new(SpotLight::new(
+                vec3(*position),
+                vec3(*direction),
+                vec3(*intensity),
+                inner_angle * PI / 180.0,
+                outer_angle * PI / 180.0,
+            ))
+        }
+        LightDescription::Directional {
+            direction,
+            irradiance,
+        } => {
+            check_finite("direction", direction)?;
+            check_finite("irradiance", irradiance)?;
+            if vec3(*direction).is_near_zero() {
+                bail!("direction: must not be zero");
+            }
+            Box::new(DirectionalLight::new(
+                vec3(*direction),
+                vec3(*irradiance),
+                radius,
+            ))
+        }
+    })
+}
+
+fn check_finite(name: &str, value: &[f64; 3]) -> Result<()> {
+    if value.iter().all(|component| component.is_finite()) {
+        Ok(())
+    } else {
+        Err(anyhow!("{name}: must be finite, found {value:?}"))
+    }
+}
+
+/// Average of a texture over a grid of texture coordinates, taken at the origin for solid
+/// textures.
+fn average(texture: &dyn Texture) -> Color {
+    const N: usize = 16;
+    let sum: Color = (0..N)
+        .flat_map(|i| (0..N).map(move |j| (i, j)))
+        .map(|(i, j)| {
+            let u = (i as f64 + 0.5) / N as f64;
+            let v = (j as f64 + 0.5) / N as f64;
+            texture.value(u, v, &Point3::default())
+        })
+        .sum();
+    sum / (N * N) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::vec3::Vec3;
+
+    use super::{
+        super::{
+            parse,
+            tests::{parse_error, CORNELL_BOX},
+        },
+        *,
+    };
+
+    #[test]
+    fn test_lights() {
+        let source = r#"
+[camera]
+look_from = [0.0, 0.0, -10.0]
+look_at = [0.0, 0.0, 0.0]
+vfov = 40.0
+
+[textures.warm]
+type = "solid"
+color = [4.0, 2.0, 1.0]
+
+[materials.light]
+type = "diffuse_light"
+color = [4.0, 4.0, 4.0]
+
+[materials.warm_light]
+type = "diffuse_light"
+texture = "warm"
+
+[materials.white]
+type = "lambertian"
+color = [0.73, 0.73, 0.73]
+
+[[objects]]
+type = "translate"
+offset = [0.0, 5.0, 0.0]
+
+[objects.object]
+type = "sphere"
+center = [0.0, 0.0, 0.0]
+radius = 1.0
+material = "light"
+
+[[objects]]
+type = "triangle"
+v0 = [-1.0, -5.0, 0.0]
+v1 = [1.0, -5.0, 0.0]
+v2 = [0.0, -5.0, 1.0]
+material = "warm_light"
+
+[[objects]]
+type = "xy_rect"
+x0 = -1.0
+x1 = 1.0
+y0 = -1.0
+y1 = 1.0
+z = 5.0
+material = "white"
+
+# Emissive, but without a way to sample it
+[[objects]]
+type = "box"
+min = [3.0, 3.0, 3.0]
+max = [4.0, 4.0, 4.0]
+material = "light"
+"#;
+        let scene = parse(source, Path::new("")).unwrap();
+        assert_eq!(4, scene.world.len());
+        assert_eq!(2, scene.lights.len());
+
+        let origin = Point3::default();
+        assert!(scene.lights.pdf_value(origin, Vec3::new(0.0, 1.0, 0.0)) > 0.0);
+        assert!(scene.lights.pdf_value(origin, Vec3::new(0.0, -5.0, 0.2)) > 0.0);
+        assert_eq!(
+            0.0,
+            scene.lights.pdf_value(origin, Vec3::new(0.0, 0.0, 1.0))
+        );
+    }
+
+    #[test]
+    fn test_analytic_lights() {
+        let source = format!(
+            "{CORNELL_BOX}{}",
+            r#"
+[[lights]]
+type = "point"
+position = [278.0, 400.0, 278.0]
+intensity = [1000.0, 1000.0, 1000.0]
+
+[[lights]]
+type = "spot"
+position = [278.0, 500.0, 278.0]
+direction = [0.0, -1.0, 0.0]
+intensity = [5000.0, 5000.0, 5000.0]
+inner_angle = 20.0
+outer_angle = 30.0
+
+[[lights]]
+type = "directional"
+direction = [1.0, -2.0, 0.5]
+irradiance = [3.0, 3.0, 3.0]
+"#
+        );
+        let scene = parse(&source, Path::new("")).unwrap();
+        // With the emissive rectangle of the box
+        assert_eq!(4, scene.lights.len());
+        // Lights without an area are never reached by a direction
+        let origin = Point3::new(278.0, 1.0, 278.0);
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        assert!(scene.lights.pdf_value(origin, up) > 0.0);
+        assert_eq!(0.0, scene.lights.pdf_value(origin, -up));
+
+        let swapped = source.replace("inner_angle = 20.0", "inner_angle = 40.0");
+        assert_eq!(
+            "lights[1]: inner_angle: must be in [0, outer_angle], found 40",
+            parse_error(&swapped)
+        );
+        let zero = source.replace(
+            "direction = [1.0, -2.0, 0.5]",
+            "direction = [0.0, 0.0, 0.0]",
+        );
+        assert_eq!("lights[2]: direction: must not be zero", parse_error(&zero));
+
+        let nan = source.replace("outer_angle = 30.0", "outer_angle = nan");
+        assert_eq!(
+            "lights[1]: outer_angle: must be in (0, 180], found NaN",
+            parse_error(&nan)
+        );
+        let nan = source.replace("inner_angle = 20.0", "inner_angle = nan");
+        assert_eq!(
+            "lights[1]: inner_angle: must be in [0, outer_angle], found NaN",
+            parse_error(&nan)
+        );
+        let infinite = source.replace(
+            "intensity = [1000.0, 1000.0, 1000.0]",
+            "intensity = [inf, 1000.0, 1000.0]",
+        );
+        assert_eq!(
+            "lights[0]: intensity: must be finite, found [inf, 1000.0, 1000.0]",
+            parse_error(&infinite)
+        );
+        let nan = source.replace(
+            "position = [278.0, 500.0, 278.0]",
+            "position = [nan, 500.0, 278.0]",
+        );
+        assert_eq!(
+            "lights[1]: position: must be finite, found [NaN, 500.0, 278.0]",
+            parse_error(&nan)
+        );
+        let nan = source.replace(
+            "irradiance = [3.0, 3.0, 3.0]",
+            "irradiance = [3.0, nan, 3.0]",
+        );
+        assert_eq!(
+            "lights[2]: irradiance: must be finite, found [3.0, NaN, 3.0]",
+            parse_error(&nan)
+        );
+    }
+}
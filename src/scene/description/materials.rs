@@ -0,0 +1,137 @@
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::{
+    materials::{Dielectric, DiffuseLight, Lambertian, Material, Metal},
+    textures::{CheckerTexture, ImageTexture, NoiseTexture, SolidColor, Texture},
+};
+
+use super::{vec3, SceneBuilder};
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub(super) enum TextureDescription {
+    Solid {
+        color: [f64; 3],
+    },
+    Checker {
+        odd: String,
+        even: String,
+    },
+    Noise {
+        #[serde(default = "default_noise_scale")]
+        scale: f64,
+    },
+    Image {
+        filename: PathBuf,
+    },
+}
+
+fn default_noise_scale() -> f64 {
+    1.0
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub(super) enum MaterialDescription {
+    Lambertian {
+        color: Option<[f64; 3]>,
+        texture: Option<String>,
+    },
+    Metal {
+        color: [f64; 3],
+        #[serde(default)]
+        fuzz: f64,
+    },
+    Dielectric {
+        ior: f64,
+    },
+    DiffuseLight {
+        color: Option<[f64; 3]>,
+        texture: Option<String>,
+    },
+}
+
+impl<'a> SceneBuilder<'a> {
+    pub(super) fn texture(&mut self, name: &'a str) -> Result<Arc<dyn Texture>> {
+        if let Some(texture) = self.textures.get(name) {
+            return Ok(texture.clone());
+        }
+        let Some(description) = self.description.textures.get(name) else {
+            bail!("unknown texture `{name}`");
+        };
+        if self.pending_textures.contains(&name) {
+            bail!("cyclic reference to texture `{name}`");
+        }
+
+        self.pending_textures.push(name);
+        let texture: Result<Arc<dyn Texture>> = match description {
+            TextureDescription::Solid { color } => Ok(Arc::new(SolidColor::new(vec3(*color)))),
+            TextureDescription::Checker { odd, even } => self.texture(odd).and_then(|odd| {
+                let even = self.texture(even)?;
+                Ok(Arc::new(CheckerTexture::new(odd, even)) as Arc<dyn Texture>)
+            }),
+            TextureDescription::Noise { scale } => {
+                Ok(Arc::new(NoiseTexture::new_with_scale(*scale)))
+            }
+            TextureDescription::Image { filename } => {
+                ImageTexture::new_with_filename(self.base_dir.join(filename))
+                    .map(|texture| Arc::new(texture) as Arc<dyn Texture>)
+                    .with_context(|| format!("failed to load {}", filename.display()))
+            }
+        };
+        self.pending_textures.pop();
+
+        let texture = texture?;
+        self.textures.insert(name, texture.clone());
+        Ok(texture)
+    }
+
+    /// Resolves the `color` or `texture` field of a description, one of which must be given.
+    pub(super) fn color_or_texture(
+        &mut self,
+        color: Option<[f64; 3]>,
+        texture: &'a Option<String>,
+    ) -> Result<Arc<dyn Texture>> {
+        match (color, texture) {
+            (Some(color), None) => Ok(Arc::new(SolidColor::new(vec3(color)))),
+            (None, Some(texture)) => self.texture(texture),
+            (Some(_), Some(_)) => bail!("only one of `color` and `texture` can be given"),
+            (None, None) => bail!("either `color` or `texture` is required"),
+        }
+    }
+
+    pub(super) fn material(&mut self, name: &'a str) -> Result<Arc<dyn Material>> {
+        if let Some(material) = self.materials.get(name) {
+            return Ok(material.clone());
+        }
+        let Some(description) = self.description.materials.get(name) else {
+            bail!("unknown material `{name}`");
+        };
+
+        let material: Arc<dyn Material> = match description {
+            MaterialDescription::Lambertian { color, texture } => {
+                Arc::new(Lambertian::new(self.color_or_texture(*color, texture)?))
+            }
+            MaterialDescription::Metal { color, fuzz } => {
+                if !(0.0..=1.0).contains(fuzz) {
+                    bail!("fuzz: must be in [0, 1], found {fuzz}");
+                }
+                Arc::new(Metal::new(vec3(*color), *fuzz))
+            }
+            MaterialDescription::Dielectric { ior } => {
+                if *ior <= 0.0 {
+                    bail!("ior: must be positive, found {ior}");
+                }
+                Arc::new(Dielectric::new(*ior))
+            }
+            MaterialDescription::DiffuseLight { color, texture } => {
+                Arc::new(DiffuseLight::new(self.color_or_texture(*color, texture)?))
+            }
+        };
+        self.materials.insert(name, material.clone());
+        Ok(material)
+    }
+}
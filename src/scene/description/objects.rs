@@ -0,0 +1,1019 @@
+use std::{f64::consts::PI, path::PathBuf, sync::Arc};
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::{
+    hittables::{
+        Aabb, AnimatedTransform, Box as HittableBox, BvhTree, Cone, ConstantMedium, Csg,
+        CsgOperation, Curve, CurveShape, Cylinder, Disk, DistanceField, Heightfield, Hittable,
+        HittableVec, Instance, MovingSphere, Paraboloid, Plane, Quad, RotateY, Sphere, Torus,
+        Transform, Translate, Triangle, XyRect, XzRect, YzRect,
+    },
+    loaders::{load_obj, load_ply, load_ply_with_vertex_colors, load_stl},
+};
+
+use super::{
+    check_positive, check_range,
+    sdf::{build_sdf, SdfDescription},
+    transforms::{build_keyframes, default_scale, transform_matrix, KeyframeDescription},
+    vec3, SceneBuilder,
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub(super) enum ObjectDescription {
+    Sphere {
+        center: [f64; 3],
+        radius: f64,
+        material: String,
+    },
+    MovingSphere {
+        center0: [f64; 3],
+        center1: [f64; 3],
+        time0: f64,
+        time1: f64,
+        radius: f64,
+        material: String,
+    },
+    /// Disk in the xz plane facing +y, with a hole of `inner_radius` in the middle
+    Disk {
+        center: [f64; 3],
+        radius: f64,
+        #[serde(default)]
+        inner_radius: f64,
+        material: String,
+    },
+    /// Cylinder standing on `base` along +y
+    Cylinder {
+        base: [f64; 3],
+        radius: f64,
+        height: f64,
+        #[serde(default = "default_capped")]
+        capped: bool,
+        material: String,
+    },
+    /// Cone standing on `base` along +y, with its apex at `height`
+    Cone {
+        base: [f64; 3],
+        radius: f64,
+        height: f64,
+        #[serde(default = "default_capped")]
+        capped: bool,
+        material: String,
+    },
+    /// Paraboloid with its vertex at `base`, opening along +y
+    Paraboloid {
+        base: [f64; 3],
+        radius: f64,
+        height: f64,
+        #[serde(default = "default_capped")]
+        capped: bool,
+        material: String,
+    },
+    /// Torus around the y axis through `center`
+    Torus {
+        center: [f64; 3],
+        major_radius: f64,
+        minor_radius: f64,
+        material: String,
+    },
+    /// Parallelogram with the corner `q` and the edges `u` and `v`, facing `u × v`
+    Quad {
+        q: [f64; 3],
+        u: [f64; 3],
+        v: [f64; 3],
+        material: String,
+    },
+    /// Infinite plane, which cannot be placed in a `bvh` or used as a model
+    Plane {
+        point: [f64; 3],
+        normal: [f64; 3],
+        material: String,
+    },
+    /// Thin cubic curve such as a hair, whose width changes linearly from one end to the other;
+    /// a ribbon facing `normals` at its ends if they are given, otherwise a round tube
+    Curve {
+        points: [[f64; 3]; 4],
+        #[serde(default)]
+        basis: CurveBasisDescription,
+        widths: [f64; 2],
+        normals: Option<[[f64; 3]; 2]>,
+        material: String,
+    },
+    /// Surface of a signed distance function, cut off outside the box from `min` to `max`
+    Sdf {
+        shape: SdfDescription,
+        min: [f64; 3],
+        max: [f64; 3],
+        material: String,
+    },
+    /// Terrain rising by `size.y` from black to white pixels of a grayscale image, which covers
+    /// `size.x` and `size.z` from `min` with its top row toward -z
+    Heightfield {
+        filename: PathBuf,
+        min: [f64; 3],
+        size: [f64; 3],
+        material: String,
+    },
+    XyRect {
+        x0: f64,
+        x1: f64,
+        y0: f64,
+        y1: f64,
+        z: f64,
+        material: String,
+    },
+    XzRect {
+        x0: f64,
+        x1: f64,
+        z0: f64,
+        z1: f64,
+        y: f64,
+        material: String,
+    },
+    YzRect {
+        y0: f64,
+        y1: f64,
+        z0: f64,
+        z1: f64,
+        x: f64,
+        material: String,
+    },
+    #[serde(rename = "box")]
+    HittableBox {
+        min: [f64; 3],
+        max: [f64; 3],
+        material: String,
+    },
+    Triangle {
+        v0: [f64; 3],
+        v1: [f64; 3],
+        v2: [f64; 3],
+        material: String,
+    },
+    /// Materials come from the MTL files referenced by the OBJ file
+    Obj {
+        filename: PathBuf,
+    },
+    /// Vertex colors are used if no material is given
+    Ply {
+        filename: PathBuf,
+        material: Option<String>,
+    },
+    Stl {
+        filename: PathBuf,
+        material: String,
+    },
+    Translate {
+        offset: [f64; 3],
+        object: Box<ObjectDescription>,
+    },
+    RotateY {
+        /// Rotation angle in degrees
+        angle: f64,
+        object: Box<ObjectDescription>,
+    },
+    /// Scales the object first, then rotates it around the x, y and z axes, then translates it
+    Transform {
+        object: Box<ObjectDescription>,
+        #[serde(default)]
+        translate: [f64; 3],
+        /// Rotation angles in degrees
+        #[serde(default)]
+        rotate: [f64; 3],
+        #[serde(default = "default_scale")]
+        scale: [f64; 3],
+    },
+    /// Moves the object between keyframes, interpolated by the time of each ray
+    Animated {
+        object: Box<ObjectDescription>,
+        keyframes: Vec<KeyframeDescription>,
+    },
+    /// Combines two closed objects, such as `a` with `b` carved out of it
+    Csg {
+        operation: CsgOperationDescription,
+        a: Box<ObjectDescription>,
+        b: Box<ObjectDescription>,
+    },
+    ConstantMedium {
+        boundary: Box<ObjectDescription>,
+        density: f64,
+        color: Option<[f64; 3]>,
+        texture: Option<String>,
+    },
+    Bvh {
+        objects: Vec<ObjectDescription>,
+    },
+    /// Places a model, scaled first, then rotated around the x, y and z axes, then translated
+    Instance {
+        model: String,
+        #[serde(default)]
+        translate: [f64; 3],
+        /// Rotation angles in degrees
+        #[serde(default)]
+        rotate: [f64; 3],
+        #[serde(default = "default_scale")]
+        scale: [f64; 3],
+        /// Overrides the materials of the model
+        material: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(super) enum CsgOperationDescription {
+    Union,
+    Intersection,
+    Difference,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(super) enum CurveBasisDescription {
+    /// Through the first and the last points
+    #[default]
+    Bezier,
+    /// Uniform B-spline segment
+    BSpline,
+}
+
+fn default_capped() -> bool {
+    true
+}
+
+impl<'a> SceneBuilder<'a> {
+    pub(super) fn model(&mut self, name: &'a str) -> Result<Arc<BvhTree>> {
+        if let Some(model) = self.models.get(name) {
+            return Ok(model.clone());
+        }
+        let Some(description) = self.description.models.get(name) else {
+            bail!("unknown model `{name}`");
+        };
+        if self.pending_models.contains(&name) {
+            bail!("cyclic reference to model `{name}`");
+        }
+
+        self.pending_models.push(name);
+        let object = self.object(description);
+        self.pending_models.pop();
+
+        let object = object?;
+        let camera = &self.description.camera;
+        if object.bounding_box(camera.time0, camera.time1).is_none() {
+            bail!("has no bounding box");
+        }
+        let model = Arc::new(BvhTree::new(vec![object], camera.time0, camera.time1));
+        self.models.insert(name, model.clone());
+        Ok(model)
+    }
+
+    pub(super) fn object(
+        &mut self,
+        description: &'a ObjectDescription,
+    ) -> Result<Box<dyn Hittable>> {
+        let camera = &self.description.camera;
+        Ok(match description {
+            ObjectDescription::Sphere {
+                center,
+                radius,
+                material,
+            } => {
+                check_positive("radius", *radius)?;
+                Box::new(Sphere::new(
+                    vec3(*center),
+                    *radius,
+                    self.material(material)?,
+                ))
+            }
+            ObjectDescription::MovingSphere {
+                center0,
+                center1,
+                time0,
+                time1,
+                radius,
+                material,
+            } => {
+                check_positive("radius", *radius)?;
+                if time0 >= time1 {
+                    bail!("time0 must be less than time1");
+                }
+                Box::new(MovingSphere::new(
+                    vec3(*center0),
+                    vec3(*center1),
+                    *time0,
+                    *time1,
+                    *radius,
+                    self.material(material)?,
+                ))
+            }
+            ObjectDescription::Disk {
+                center,
+                radius,
+                inner_radius,
+                material,
+            } => {
+                check_positive("radius", *radius)?;
+                if !(0.0..*radius).contains(inner_radius) {
+                    bail!("inner_radius: must be in [0, radius), found {inner_radius}");
+                }
+                Box::new(Disk::new_with_inner_radius(
+                    vec3(*center),
+                    *radius,
+                    *inner_radius,
+                    self.material(material)?,
+                ))
+            }
+            ObjectDescription::Cylinder {
+                base,
+                radius,
+                height,
+                capped,
+                material,
+            } => {
+                check_positive("radius", *radius)?;
+                check_positive("height", *height)?;
+                let material = self.material(material)?;
+                if *capped {
+                    Box::new(Cylinder::new(vec3(*base), *radius, *height, material))
+                } else {
+                    Box::new(Cylinder::new_uncapped(
+                        vec3(*base),
+                        *radius,
+                        *height,
+                        material,
+                    ))
+                }
+            }
+            ObjectDescription::Cone {
+                base,
+                radius,
+                height,
+                capped,
+                material,
+            } => {
+                check_positive("radius", *radius)?;
+                check_positive("height", *height)?;
+                let material = self.material(material)?;
+                if *capped {
+                    Box::new(Cone::new(vec3(*base), *radius, *height, material))
+                } else {
+                    Box::new(Cone::new_uncapped(vec3(*base), *radius, *height, material))
+                }
+            }
+            ObjectDescription::Paraboloid {
+                base,
+                radius,
+                height,
+                capped,
+                material,
+            } => {
+                check_positive("radius", *radius)?;
+                check_positive("height", *height)?;
+                let material = self.material(material)?;
+                if *capped {
+                    Box::new(Paraboloid::new(vec3(*base), *radius, *height, material))
+                } else {
+                    Box::new(Paraboloid::new_uncapped(
+                        vec3(*base),
+                        *radius,
+                        *height,
+                        material,
+                    ))
+                }
+            }
+            ObjectDescription::Torus {
+                center,
+                major_radius,
+                minor_radius,
+                material,
+            } => {
+                check_positive("minor_radius", *minor_radius)?;
+                check_positive("major_radius", *major_radius)?;
+                if major_radius <= minor_radius {
+                    bail!(
+                        "major_radius: must be greater than minor_radius {minor_radius}, found {major_radius}"
+                    );
+                }
+                Box::new(Torus::new(
+                    vec3(*center),
+                    *major_radius,
+                    *minor_radius,
+                    self.material(material)?,
+                ))
+            }
+            ObjectDescription::Quad { q, u, v, material } => {
+                if vec3(*u).cross(vec3(*v)).is_near_zero() {
+                    bail!("u, v: the edges must not be parallel");
+                }
+                Box::new(Quad::new(
+                    vec3(*q),
+                    vec3(*u),
+                    vec3(*v),
+                    self.material(material)?,
+                ))
+            }
+            ObjectDescription::Plane {
+                point,
+                normal,
+                material,
+            } => {
+                if vec3(*normal).is_near_zero() {
+                    bail!("normal: must not be zero");
+                }
+                Box::new(Plane::new(
+                    vec3(*point),
+                    vec3(*normal),
+                    self.material(material)?,
+                ))
+            }
+            ObjectDescription::Curve {
+                points,
+                basis,
+                widths,
+                normals,
+                material,
+            } => {
+                if !widths.iter().all(|&width| width >= 0.0) {
+                    bail!("widths: must not be negative, found {widths:?}");
+                }
+                let shape = match normals {
+                    Some(normals @ [normal0, normal1]) => {
+                        if !normals
+                            .iter()
+                            .flatten()
+                            .all(|component| component.is_finite())
+                        {
+                            bail!("normals: must be finite, found {normals:?}");
+                        }
+                        if vec3(*normal0).is_near_zero() || vec3(*normal1).is_near_zero() {
+                            bail!("normals: must not be zero");
+                        }
+                        CurveShape::Ribbon {
+                            normal0: vec3(*normal0),
+                            normal1: vec3(*normal1),
+                        }
+                    }
+                    None => CurveShape::Round,
+                };
+                let points = points.map(vec3);
+                let [width0, width1] = *widths;
+                let material = self.material(material)?;
+                Box::new(match basis {
+                    CurveBasisDescription::Bezier => {
+                        Curve::new(points, width0, width1, shape, material)
+                    }
+                    CurveBasisDescription::BSpline => {
+                        Curve::new_b_spline(points, width0, width1, shape, material)
+                    }
+                })
+            }
+            ObjectDescription::Sdf {
+                shape,
+                min,
+                max,
+                material,
+            } => {
+                for (axis, (min, max)) in ["x", "y", "z"].iter().zip(min.iter().zip(max)) {
+                    check_range(axis, *min, *max)?;
+                }
+                Box::new(DistanceField::new(
+                    build_sdf(shape).context("shape")?,
+                    Aabb::new(vec3(*min), vec3(*max)),
+                    self.material(material)?,
+                ))
+            }
+            ObjectDescription::Heightfield {
+                filename,
+                min,
+                size,
+                material,
+            } => {
+                if !size.iter().all(|&size| size > 0.0) {
+                    bail!("size: must be positive, found {size:?}");
+                }
+                Box::new(
+                    Heightfield::new_with_filename(
+                        self.base_dir.join(filename),
+                        vec3(*min),
+                        vec3(*size),
+                        self.material(material)?,
+                    )
+                    .with_context(|| format!("failed to load {}", filename.display()))?,
+                )
+            }
+            ObjectDescription::XyRect {
+                x0,
+                x1,
+                y0,
+                y1,
+                z,
+                material,
+            } => {
+                check_range("x", *x0, *x1)?;
+                check_range("y", *y0, *y1)?;
+                Box::new(XyRect::new(
+                    *x0,
+                    *x1,
+                    *y0,
+                    *y1,
+                    *z,
+                    self.material(material)?,
+                ))
+            }
+            ObjectDescription::XzRect {
+                x0,
+                x1,
+                z0,
+                z1,
+                y,
+                material,
+            } => {
+                check_range("x", *x0, *x1)?;
+                check_range("z", *z0, *z1)?;
+                Box::new(XzRect::new(
+                    *x0,
+                    *x1,
+                    *z0,
+                    *z1,
+                    *y,
+                    self.material(material)?,
+                ))
+            }
+            ObjectDescription::YzRect {
+                y0,
+                y1,
+                z0,
+                z1,
+                x,
+                material,
+            } => {
+                check_range("y", *y0, *y1)?;
+                check_range("z", *z0, *z1)?;
+                Box::new(YzRect::new(
+                    *y0,
+                    *y1,
+                    *z0,
+                    *z1,
+                    *x,
+                    self.material(material)?,
+                ))
+            }
+            ObjectDescription::HittableBox { min, max, material } => {
+                for (axis, (min, max)) in ["x", "y", "z"].iter().zip(min.iter().zip(max)) {
+                    check_range(axis, *min, *max)?;
+                }
+                Box::new(HittableBox::new(
+                    vec3(*min),
+                    vec3(*max),
+                    self.material(material)?,
+                ))
+            }
+            ObjectDescription::Triangle {
+                v0,
+                v1,
+                v2,
+                material,
+            } => Box::new(Triangle::new(
+                vec3(*v0),
+                vec3(*v1),
+                vec3(*v2),
+                self.material(material)?,
+            )),
+            ObjectDescription::Obj { filename } => {
+                let meshes = load_obj(self.base_dir.join(filename))?;
+                Box::new(BvhTree::new(meshes, camera.time0, camera.time1))
+            }
+            ObjectDescription::Ply { filename, material } => {
+                let filename = self.base_dir.join(filename);
+                match material {
+                    Some(material) => Box::new(load_ply(filename, self.material(material)?)?),
+                    None => Box::new(load_ply_with_vertex_colors(filename)?),
+                }
+            }
+            ObjectDescription::Stl { filename, material } => Box::new(load_stl(
+                self.base_dir.join(filename),
+                self.material(material)?,
+            )?),
+            ObjectDescription::Translate { offset, object } => Box::new(Translate::new(
+                self.object(object).context("object")?,
+                vec3(*offset),
+            )),
+            ObjectDescription::RotateY { angle, object } => Box::new(RotateY::new(
+                self.object(object).context("object")?,
+                angle * PI / 180.0,
+            )),
+            ObjectDescription::Transform {
+                object,
+                translate,
+                rotate,
+                scale,
+            } => Box::new(Transform::new(
+                self.object(object).context("object")?,
+                transform_matrix(translate, rotate, scale)?,
+            )),
+            ObjectDescription::Animated { object, keyframes } => {
+                let keyframes = build_keyframes(keyframes)?;
+                Box::new(AnimatedTransform::new(
+                    self.object(object).context("object")?,
+                    keyframes,
+                ))
+            }
+            ObjectDescription::Csg { operation, a, b } => {
+                let operation = match operation {
+                    CsgOperationDescription::Union => CsgOperation::Union,
+                    CsgOperationDescription::Intersection => CsgOperation::Intersection,
+                    CsgOperationDescription::Difference => CsgOperation::Difference,
+                };
+                Box::new(Csg::new(
+                    self.object(a).context("a")?,
+                    self.object(b).context("b")?,
+                    operation,
+                ))
+            }
+            ObjectDescription::ConstantMedium {
+                boundary,
+                density,
+                color,
+                texture,
+            } => {
+                check_positive("density", *density)?;
+                let boundary = self.object(boundary).context("boundary")?;
+                let texture = self.color_or_texture(*color, texture)?;
+                Box::new(ConstantMedium::new(boundary, texture, *density))
+            }
+            ObjectDescription::Bvh { objects } => {
+                if objects.is_empty() {
+                    bail!("objects: at least one object is required");
+                }
+                let objects = objects
+                    .iter()
+                    .enumerate()
+                    .map(|(i, object)| self.object(object).with_context(|| format!("objects[{i}]")))
+                    .collect::<Result<HittableVec>>()?;
+                if let Some(i) = objects
+                    .iter()
+                    .position(|object| object.bounding_box(camera.time0, camera.time1).is_none())
+                {
+                    bail!("objects[{i}]: has no bounding box");
+                }
+                Box::new(BvhTree::new(objects, camera.time0, camera.time1))
+            }
+            ObjectDescription::Instance {
+                model,
+                translate,
+                rotate,
+                scale,
+                material,
+            } => {
+                let transform = transform_matrix(translate, rotate, scale)?;
+                let model = self.model(model)?;
+                match material {
+                    Some(material) => Box::new(Instance::new_with_material(
+                        model,
+                        transform,
+                        self.material(material)?,
+                    )),
+                    None => Box::new(Instance::new(model, transform)),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::{
+        ray::Ray,
+        vec3::{Color, Vec3},
+    };
+
+    use super::{
+        super::{parse, tests::parse_error},
+        *,
+    };
+
+    #[test]
+    fn test_instances() {
+        let source = r#"
+[camera]
+look_from = [0.0, 0.0, -10.0]
+look_at = [0.0, 0.0, 0.0]
+vfov = 40.0
+
+[materials.white]
+type = "lambertian"
+color = [0.73, 0.73, 0.73]
+
+[materials.light]
+type = "diffuse_light"
+color = [4.0, 4.0, 4.0]
+
+[models.ball]
+type = "sphere"
+center = [0.0, 0.0, 0.0]
+radius = 1.0
+material = "white"
+
+[[objects]]
+type = "instance"
+model = "ball"
+translate = [-3.0, 0.0, 0.0]
+
+[[objects]]
+type = "instance"
+model = "ball"
+translate = [3.0, 0.0, 0.0]
+rotate = [0.0, 90.0, 0.0]
+scale = [2.0, 1.0, 1.0]
+material = "light"
+"#;
+        let scene = parse(source, Path::new("")).unwrap();
+        let hit = |x: f64| {
+            let ray = Ray::new(Vec3::new(x, 0.0, -10.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+            let hit = scene.world.hit(&ray, 0.001, f64::INFINITY).unwrap();
+            (hit.t, hit.material.emitted(hit.u, hit.v, &hit.p))
+        };
+
+        assert_eq!((9.0, Color::default()), round(hit(-3.0)));
+        // Scaled along x and then rotated, so the second ball is stretched along z
+        assert_eq!((8.0, Color::new(4.0, 4.0, 4.0)), round(hit(3.0)));
+
+        let unknown = source.replace(
+            "model = \"ball\"\ntranslate = [-3.0",
+            "model = \"cube\"\ntranslate = [-3.0",
+        );
+        assert_eq!("objects[0]: unknown model `cube`", parse_error(&unknown));
+
+        let nan = source.replace("radius = 1.0", "radius = nan");
+        assert_eq!(
+            "models.ball: radius: must be positive, found NaN",
+            parse_error(&nan)
+        );
+
+        let cyclic = source.replace(
+            "type = \"sphere\"\ncenter = [0.0, 0.0, 0.0]\nradius = 1.0\nmaterial = \"white\"",
+            "type = \"instance\"\nmodel = \"ball\"",
+        );
+        assert_eq!(
+            "models.ball: cyclic reference to model `ball`",
+            parse_error(&cyclic)
+        );
+    }
+
+    #[test]
+    fn test_csg() {
+        let source = r#"
+[camera]
+look_from = [0.0, 0.0, -10.0]
+look_at = [0.0, 0.0, 0.0]
+vfov = 40.0
+
+[materials.white]
+type = "lambertian"
+color = [0.73, 0.73, 0.73]
+
+[[objects]]
+type = "csg"
+operation = "difference"
+
+[objects.a]
+type = "sphere"
+center = [0.0, 0.0, 0.0]
+radius = 1.0
+material = "white"
+
+[objects.b]
+type = "box"
+min = [-0.5, -0.5, -2.0]
+max = [0.5, 0.5, 2.0]
+material = "white"
+"#;
+        let scene = parse(source, Path::new("")).unwrap();
+        let hit = |x: f64| {
+            let ray = Ray::new(Vec3::new(x, 0.0, -10.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+            scene.world.hit(&ray, 0.001, f64::INFINITY).is_some()
+        };
+
+        assert!(!hit(0.0));
+        assert!(hit(0.75));
+    }
+
+    #[test]
+    fn test_quadrics() {
+        let source = r#"
+[camera]
+look_from = [0.0, 0.0, -10.0]
+look_at = [0.0, 0.0, 0.0]
+vfov = 40.0
+
+[materials.white]
+type = "lambertian"
+color = [0.73, 0.73, 0.73]
+
+[[objects]]
+type = "disk"
+center = [-4.0, 0.0, 0.0]
+radius = 1.0
+inner_radius = 0.5
+material = "white"
+
+[[objects]]
+type = "cylinder"
+base = [-2.0, -1.0, 0.0]
+radius = 0.5
+height = 2.0
+material = "white"
+
+[[objects]]
+type = "cone"
+base = [0.0, -1.0, 0.0]
+radius = 0.5
+height = 2.0
+capped = false
+material = "white"
+
+[[objects]]
+type = "paraboloid"
+base = [2.0, -1.0, 0.0]
+radius = 0.5
+height = 2.0
+material = "white"
+
+[[objects]]
+type = "torus"
+center = [5.0, 0.0, 0.0]
+major_radius = 1.0
+minor_radius = 0.25
+material = "white"
+"#;
+        let scene = parse(source, Path::new("")).unwrap();
+        let hit = |x: f64| {
+            let ray = Ray::new(Vec3::new(x, 0.0, -10.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+            scene.world.hit(&ray, 0.001, f64::INFINITY).map(|hit| hit.t)
+        };
+
+        // The disk faces +y, so it is seen edge-on
+        assert_eq!(None, hit(-4.0));
+        assert!((hit(-2.0).unwrap() - 9.5).abs() < 1e-9);
+        assert!((hit(0.0).unwrap() - 9.75).abs() < 1e-9);
+        assert!((hit(2.0).unwrap() - (10.0 - 0.125f64.sqrt())).abs() < 1e-9);
+        assert!((hit(5.0).unwrap() - 8.75).abs() < 1e-9);
+
+        let invalid = source.replace("minor_radius = 0.25", "minor_radius = 1.5");
+        assert_eq!(
+            "objects[4]: major_radius: must be greater than minor_radius 1.5, found 1",
+            parse_error(&invalid)
+        );
+        let invalid = source.replace("major_radius = 1.0", "major_radius = nan");
+        assert_eq!(
+            "objects[4]: major_radius: must be positive, found NaN",
+            parse_error(&invalid)
+        );
+    }
+
+    #[test]
+    fn test_quad_and_plane() {
+        let source = r#"
+[camera]
+look_from = [0.0, 0.0, -10.0]
+look_at = [0.0, 0.0, 0.0]
+vfov = 40.0
+
+[materials.white]
+type = "lambertian"
+color = [0.73, 0.73, 0.73]
+
+[materials.light]
+type = "diffuse_light"
+color = [4.0, 4.0, 4.0]
+
+[[objects]]
+type = "quad"
+q = [-1.0, -1.0, 0.0]
+u = [2.0, 0.0, 0.0]
+v = [0.0, 2.0, 2.0]
+material = "light"
+
+[[objects]]
+type = "plane"
+point = [0.0, 0.0, 5.0]
+normal = [0.0, 0.0, -1.0]
+material = "white"
+"#;
+        let scene = parse(source, Path::new("")).unwrap();
+        let hit = |y: f64| {
+            let ray = Ray::new(Vec3::new(0.0, y, -10.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+            let hit = scene.world.hit(&ray, 0.001, f64::INFINITY).unwrap();
+            (hit.t, hit.material.emitted(hit.u, hit.v, &hit.p))
+        };
+
+        assert_eq!((11.0, Color::new(4.0, 4.0, 4.0)), round(hit(0.0)));
+        assert_eq!((15.0, Color::default()), round(hit(-2.0)));
+
+        let parallel = source.replace("v = [0.0, 2.0, 2.0]", "v = [1.0, 0.0, 0.0]");
+        assert_eq!(
+            "objects[0]: u, v: the edges must not be parallel",
+            parse_error(&parallel)
+        );
+    }
+
+    #[test]
+    fn test_heightfield() {
+        let source = r#"
+[camera]
+look_from = [0.0, 0.0, -10.0]
+look_at = [0.0, 0.0, 0.0]
+vfov = 40.0
+
+[materials.white]
+type = "lambertian"
+color = [0.73, 0.73, 0.73]
+
+[[objects]]
+type = "heightfield"
+filename = "missing.png"
+min = [-100.0, 0.0, -100.0]
+size = [200.0, 50.0, 200.0]
+material = "white"
+"#;
+        assert!(parse_error(source).starts_with("objects[0]: failed to load missing.png: "));
+
+        let flat = source.replace("50.0", "0.0");
+        assert_eq!(
+            "objects[0]: size: must be positive, found [200.0, 0.0, 200.0]",
+            parse_error(&flat)
+        );
+
+        let nan = source.replace("50.0", "nan");
+        assert_eq!(
+            "objects[0]: size: must be positive, found [200.0, NaN, 200.0]",
+            parse_error(&nan)
+        );
+    }
+
+    #[test]
+    fn test_curve() {
+        let source = r#"
+[camera]
+look_from = [0.0, 0.0, -10.0]
+look_at = [0.0, 0.0, 0.0]
+vfov = 40.0
+
+[materials.white]
+type = "lambertian"
+color = [0.73, 0.73, 0.73]
+
+[[objects]]
+type = "curve"
+points = [[-3.0, -1.0, 0.0], [-1.0, 1.0, 0.0], [1.0, -1.0, 0.0], [3.0, 1.0, 0.0]]
+basis = "b_spline"
+widths = [0.2, 0.2]
+material = "white"
+
+[[objects]]
+type = "curve"
+points = [[-1.0, 2.0, 0.0], [-0.5, 2.0, 0.0], [0.5, 2.0, 0.0], [1.0, 2.0, 0.0]]
+widths = [0.2, 0.2]
+normals = [[0.0, 0.0, 1.0], [0.0, 0.0, 1.0]]
+material = "white"
+"#;
+        let scene = parse(source, Path::new("")).unwrap();
+        let hit = |y: f64| {
+            let ray = Ray::new(Vec3::new(0.0, y, -10.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+            scene.world.hit(&ray, 0.001, f64::INFINITY).map(|hit| hit.t)
+        };
+
+        // The B-spline segment passes through the origin, and the ribbon lies flat in z = 0
+        assert!((hit(0.0).unwrap() - 9.9).abs() < 1e-9);
+        assert!((hit(2.0).unwrap() - 10.0).abs() < 1e-9);
+        assert_eq!(None, hit(1.0));
+
+        let invalid = source.replace("[0.0, 0.0, 1.0]]", "[0.0, 0.0, 0.0]]");
+        assert_eq!(
+            "objects[1]: normals: must not be zero",
+            parse_error(&invalid)
+        );
+
+        let invalid = source.replace("[0.0, 0.0, 1.0]]", "[0.0, nan, 1.0]]");
+        assert_eq!(
+            "objects[1]: normals: must be finite, found [[0.0, 0.0, 1.0], [0.0, NaN, 1.0]]",
+            parse_error(&invalid)
+        );
+
+        let invalid = source.replace(
+            "widths = [0.2, 0.2]\nnormals",
+            "widths = [nan, 0.2]\nnormals",
+        );
+        assert_eq!(
+            "objects[1]: widths: must not be negative, found [NaN, 0.2]",
+            parse_error(&invalid)
+        );
+    }
+
+    fn round((t, color): (f64, Color)) -> (f64, Color) {
+        ((t * 1e9).round() / 1e9, color)
+    }
+}
@@ -0,0 +1,181 @@
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::{
+    sdf::{self, Mandelbulb, RoundBox, Sdf},
+    vec3::Point3,
+};
+
+use super::{check_positive, vec3};
+
+/// Signed distance function built from shapes centered at the origin
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub(super) enum SdfDescription {
+    Sphere {
+        radius: f64,
+    },
+    /// Box with its edges rounded by `radius`, which is included in `half_extents`
+    RoundBox {
+        half_extents: [f64; 3],
+        #[serde(default)]
+        radius: f64,
+    },
+    Mandelbulb {
+        #[serde(default = "default_mandelbulb_power")]
+        power: f64,
+        #[serde(default = "default_mandelbulb_iterations")]
+        iterations: usize,
+    },
+    Union {
+        a: Box<SdfDescription>,
+        b: Box<SdfDescription>,
+    },
+    Intersection {
+        a: Box<SdfDescription>,
+        b: Box<SdfDescription>,
+    },
+    /// `a` with `b` carved out of it
+    Difference {
+        a: Box<SdfDescription>,
+        b: Box<SdfDescription>,
+    },
+    /// Union blending the surfaces where they are closer than `k`
+    SmoothUnion {
+        a: Box<SdfDescription>,
+        b: Box<SdfDescription>,
+        k: f64,
+    },
+    Translate {
+        offset: [f64; 3],
+        shape: Box<SdfDescription>,
+    },
+}
+
+fn default_mandelbulb_power() -> f64 {
+    Mandelbulb::default().power
+}
+
+fn default_mandelbulb_iterations() -> usize {
+    Mandelbulb::default().iterations
+}
+
+pub(super) type BoxedSdf = Box<dyn Fn(Point3) -> f64 + Send + Sync>;
+
+fn boxed(sdf: impl Sdf + 'static) -> BoxedSdf {
+    Box::new(move |p| sdf.distance(p))
+}
+
+pub(super) fn build_sdf(description: &SdfDescription) -> Result<BoxedSdf> {
+    Ok(match description {
+        SdfDescription::Sphere { radius } => {
+            check_positive("radius", *radius)?;
+            boxed(sdf::Sphere::new(*radius))
+        }
+        SdfDescription::RoundBox {
+            half_extents,
+            radius,
+        } => {
+            if !half_extents.iter().all(|&half_extent| half_extent > 0.0) {
+                bail!("half_extents: must be positive, found {half_extents:?}");
+            }
+            let smallest = half_extents.iter().copied().fold(f64::INFINITY, f64::min);
+            if !(*radius >= 0.0 && *radius <= smallest) {
+                bail!("radius: must be between 0 and the smallest half extent {smallest}, found {radius}");
+            }
+            boxed(RoundBox::new(vec3(*half_extents), *radius))
+        }
+        SdfDescription::Mandelbulb { power, iterations } => {
+            if !(power.is_finite() && *power > 1.0) {
+                bail!("power: must be greater than 1, found {power}");
+            }
+            boxed(Mandelbulb::new(*power, *iterations))
+        }
+        SdfDescription::Union { a, b } => {
+            let (a, b) = (build_sdf(a).context("a")?, build_sdf(b).context("b")?);
+            boxed(a.union(b))
+        }
+        SdfDescription::Intersection { a, b } => {
+            let (a, b) = (build_sdf(a).context("a")?, build_sdf(b).context("b")?);
+            boxed(a.intersection(b))
+        }
+        SdfDescription::Difference { a, b } => {
+            let (a, b) = (build_sdf(a).context("a")?, build_sdf(b).context("b")?);
+            boxed(a.difference(b))
+        }
+        SdfDescription::SmoothUnion { a, b, k } => {
+            check_positive("k", *k)?;
+            let (a, b) = (build_sdf(a).context("a")?, build_sdf(b).context("b")?);
+            boxed(a.smooth_union(b, *k))
+        }
+        SdfDescription::Translate { offset, shape } => {
+            boxed(build_sdf(shape).context("shape")?.translate(vec3(*offset)))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::{hittables::Hittable, ray::Ray, vec3::Vec3};
+
+    use super::super::{parse, tests::parse_error};
+
+    #[test]
+    fn test_sdf() {
+        let source = r#"
+[camera]
+look_from = [0.0, 0.0, -10.0]
+look_at = [0.0, 0.0, 0.0]
+vfov = 40.0
+
+[materials.white]
+type = "lambertian"
+color = [0.73, 0.73, 0.73]
+
+[[objects]]
+type = "sdf"
+min = [-1.0, -1.0, -1.0]
+max = [1.0, 1.0, 1.0]
+material = "white"
+
+[objects.shape]
+type = "difference"
+
+[objects.shape.a]
+type = "round_box"
+half_extents = [1.0, 1.0, 1.0]
+radius = 0.1
+
+[objects.shape.b]
+type = "translate"
+offset = [0.0, 0.0, -1.0]
+
+[objects.shape.b.shape]
+type = "sphere"
+radius = 0.5
+"#;
+        let scene = parse(source, Path::new("")).unwrap();
+        let hit = |x: f64| {
+            let ray = Ray::new(Vec3::new(x, 0.0, -10.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+            scene.world.hit(&ray, 0.001, f64::INFINITY).unwrap().t
+        };
+
+        // Into the hollow carved by the sphere, and onto the front of the box beside it
+        assert!((hit(0.0) - 9.5).abs() < 1e-4);
+        assert!((hit(0.8) - 9.0).abs() < 1e-4);
+
+        let invalid = source.replace("radius = 0.1", "radius = 1.5");
+        assert_eq!(
+            "objects[0]: shape: a: radius: must be between 0 and the smallest half extent 1, found 1.5",
+            parse_error(&invalid)
+        );
+
+        let invalid = source.replace("radius = 0.1", "radius = nan");
+        assert_eq!(
+            "objects[0]: shape: a: radius: must be between 0 and the smallest half extent 1, found NaN",
+            parse_error(&invalid)
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use std::f64::consts::PI;
+
+use anyhow::{bail, Result};
+use serde::Deserialize;
+
+use crate::{hittables::Keyframe, matrix::Matrix4, quaternion::Quaternion, vec3::Vec3};
+
+use super::vec3;
+
+/// Pose at a point in time, with the same order of operations as `transform`
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct KeyframeDescription {
+    time: f64,
+    #[serde(default)]
+    translate: [f64; 3],
+    /// Rotation angles in degrees
+    #[serde(default)]
+    rotate: [f64; 3],
+    #[serde(default = "default_scale")]
+    scale: [f64; 3],
+}
+
+pub(super) fn default_scale() -> [f64; 3] {
+    [1.0, 1.0, 1.0]
+}
+
+/// Scaling, then rotation around the x, y and z axes with angles in degrees, then translation
+pub(super) fn transform_matrix(
+    translate: &[f64; 3],
+    rotate: &[f64; 3],
+    scale: &[f64; 3],
+) -> Result<Matrix4> {
+    let [x, y, z] = rotate.map(|angle| angle * PI / 180.0);
+    let matrix = Matrix4::translation(vec3(*translate))
+        * Matrix4::rotation(Vec3::new(0.0, 0.0, 1.0), z)
+        * Matrix4::rotation(Vec3::new(0.0, 1.0, 0.0), y)
+        * Matrix4::rotation(Vec3::new(1.0, 0.0, 0.0), x)
+        * Matrix4::scaling(vec3(*scale));
+    if matrix.inverse().is_none() {
+        bail!(
+            "the transform must be invertible, found translate {translate:?}, rotate {rotate:?} \
+             and scale {scale:?}"
+        );
+    }
+    Ok(matrix)
+}
+
+/// Keyframes of an `animated` object, whose scales must keep their signs so that they never pass
+/// through zero in between.
+pub(super) fn build_keyframes(descriptions: &[KeyframeDescription]) -> Result<Vec<Keyframe>> {
+    if descriptions.is_empty() {
+        bail!("keyframes: at least one keyframe is required");
+    }
+    let first = descriptions[0].scale;
+    descriptions
+        .iter()
+        .enumerate()
+        .map(|(i, keyframe)| {
+            if keyframe.scale.contains(&0.0) {
+                bail!(
+                    "keyframes[{i}]: scale: must not be zero, found {:?}",
+                    keyframe.scale
+                );
+            }
+            if (0..3).any(|k| (keyframe.scale[k] < 0.0) != (first[k] < 0.0)) {
+                bail!(
+                    "keyframes[{i}]: scale: must have the same signs as in keyframes[0], \
+                     found {:?}",
+                    keyframe.scale
+                );
+            }
+            let [x, y, z] = keyframe.rotate.map(|angle| angle * PI / 180.0);
+            Ok(Keyframe::new(
+                keyframe.time,
+                vec3(keyframe.translate),
+                Quaternion::from_euler(x, y, z),
+                vec3(keyframe.scale),
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::{hittables::Hittable, ray::Ray};
+
+    use super::{
+        super::{parse, tests::parse_error},
+        *,
+    };
+
+    #[test]
+    fn test_transform() {
+        let source = r#"
+[camera]
+look_from = [0.0, 0.0, -10.0]
+look_at = [0.0, 0.0, 0.0]
+vfov = 40.0
+
+[materials.white]
+type = "lambertian"
+color = [0.73, 0.73, 0.73]
+
+[[objects]]
+type = "transform"
+rotate = [90.0, 0.0, 0.0]
+scale = [1.0, 1.0, 3.0]
+
+[objects.object]
+type = "sphere"
+center = [0.0, 0.0, 0.0]
+radius = 1.0
+material = "white"
+"#;
+        let scene = parse(source, Path::new("")).unwrap();
+        // Stretched along z, then turned to lie along y
+        let ray = Ray::new(Vec3::new(0.0, 2.0, -10.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+        assert!(scene.world.hit(&ray, 0.001, f64::INFINITY).is_some());
+        let ray = Ray::new(Vec3::new(2.0, 0.0, -10.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+        assert!(scene.world.hit(&ray, 0.001, f64::INFINITY).is_none());
+
+        for scale in ["[1.0, 0.0, 3.0]", "[1e-13, 1.0, 3.0]", "[1.0, nan, 3.0]"] {
+            let source = source.replace("scale = [1.0, 1.0, 3.0]", &format!("scale = {scale}"));
+            let error = parse_error(&source);
+            assert!(
+                error.starts_with("objects[0]: the transform must be invertible, found "),
+                "{error}"
+            );
+        }
+        let source = source.replace("rotate = [90.0, 0.0, 0.0]", "rotate = [nan, 0.0, 0.0]");
+        assert_eq!(
+            "objects[0]: the transform must be invertible, found translate [0.0, 0.0, 0.0], \
+             rotate [NaN, 0.0, 0.0] and scale [1.0, 1.0, 3.0]",
+            parse_error(&source)
+        );
+    }
+
+    #[test]
+    fn test_animated() {
+        let source = r#"
+[camera]
+look_from = [0.0, 0.0, -10.0]
+look_at = [0.0, 0.0, 0.0]
+vfov = 40.0
+
+[materials.white]
+type = "lambertian"
+color = [0.73, 0.73, 0.73]
+
+[[objects]]
+type = "animated"
+keyframes = [
+    { time = 0.0 },
+    { time = 1.0, translate = [4.0, 0.0, 0.0], rotate = [0.0, 90.0, 0.0] },
+]
+
+[objects.object]
+type = "sphere"
+center = [0.0, 0.0, 0.0]
+radius = 1.0
+material = "white"
+"#;
+        let scene = parse(source, Path::new("")).unwrap();
+        let hit = |x: f64, time: f64| {
+            let ray = Ray::new(Vec3::new(x, 0.0, -10.0), Vec3::new(0.0, 0.0, 1.0), time);
+            scene.world.hit(&ray, 0.001, f64::INFINITY).is_some()
+        };
+
+        assert!(hit(0.0, 0.0) && !hit(4.0, 0.0));
+        assert!(hit(2.0, 0.5));
+        assert!(!hit(0.0, 1.0) && hit(4.0, 1.0));
+
+        let start = source.find("keyframes = [").unwrap();
+        let end = source.find("\n\n[objects.object]").unwrap();
+        let empty = format!("{}keyframes = []{}", &source[..start], &source[end..]);
+        assert_eq!(
+            "objects[0]: keyframes: at least one keyframe is required",
+            parse_error(&empty)
+        );
+        let mirrored = source.replace(
+            "{ time = 0.0 }",
+            "{ time = 0.0 }, { time = 0.5, scale = [-1.0, 1.0, 1.0] }",
+        );
+        assert_eq!(
+            "objects[0]: keyframes[1]: scale: must have the same signs as in keyframes[0], \
+             found [-1.0, 1.0, 1.0]",
+            parse_error(&mirrored)
+        );
+    }
+}
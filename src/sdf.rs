@@ -0,0 +1,270 @@
+//! Signed distance functions for `DistanceField`, and operations combining them.
+//!
+//! The distance may be underestimated, which only makes sphere tracing take more steps, but it
+//! must never be overestimated.
+
+use crate::vec3::{Point3, Vec3};
+
+/// Signed distance to a surface, negative inside.
+pub trait Sdf: Send + Sync {
+    fn distance(&self, p: Point3) -> f64;
+
+    fn union<B: Sdf>(self, other: B) -> Union<Self, B>
+    where
+        Self: Sized,
+    {
+        Union { a: self, b: other }
+    }
+
+    fn intersection<B: Sdf>(self, other: B) -> Intersection<Self, B>
+    where
+        Self: Sized,
+    {
+        Intersection { a: self, b: other }
+    }
+
+    /// `self` with `other` carved out of it.
+    fn difference<B: Sdf>(self, other: B) -> Difference<Self, B>
+    where
+        Self: Sized,
+    {
+        Difference { a: self, b: other }
+    }
+
+    /// Union blending the surfaces where they are closer than `k`.
+    fn smooth_union<B: Sdf>(self, other: B, k: f64) -> SmoothUnion<Self, B>
+    where
+        Self: Sized,
+    {
+        SmoothUnion {
+            a: self,
+            b: other,
+            k,
+        }
+    }
+
+    fn translate(self, offset: Vec3) -> Translate<Self>
+    where
+        Self: Sized,
+    {
+        Translate { sdf: self, offset }
+    }
+}
+
+/// Any distance function, including boxed ones such as `Box<dyn Fn(Point3) -> f64 + Send + Sync>`.
+impl<F: Fn(Point3) -> f64 + Send + Sync> Sdf for F {
+    fn distance(&self, p: Point3) -> f64 {
+        self(p)
+    }
+}
+
+/// Sphere centered at the origin.
+#[derive(Debug, Clone)]
+pub struct Sphere {
+    pub radius: f64,
+}
+
+impl Sphere {
+    pub fn new(radius: f64) -> Sphere {
+        Sphere { radius }
+    }
+}
+
+impl Sdf for Sphere {
+    fn distance(&self, p: Point3) -> f64 {
+        p.length() - self.radius
+    }
+}
+
+/// Box centered at the origin with its edges rounded by `radius`, which is included in
+/// `half_extents`.
+#[derive(Debug, Clone)]
+pub struct RoundBox {
+    pub half_extents: Vec3,
+    pub radius: f64,
+}
+
+impl RoundBox {
+    pub fn new(half_extents: Vec3, radius: f64) -> RoundBox {
+        RoundBox {
+            half_extents,
+            radius,
+        }
+    }
+}
+
+impl Sdf for RoundBox {
+    fn distance(&self, p: Point3) -> f64 {
+        let inner = self.half_extents - Vec3::new(self.radius, self.radius, self.radius);
+        let q = Vec3::new(
+            p.x().abs() - inner.x(),
+            p.y().abs() - inner.y(),
+            p.z().abs() - inner.z(),
+        );
+        let outside = Vec3::new(q.x().max(0.0), q.y().max(0.0), q.z().max(0.0)).length();
+        let inside = q.x().max(q.y()).max(q.z()).min(0.0);
+        outside + inside - self.radius
+    }
+}
+
+/// Mandelbulb fractal centered at the origin, within a radius of about 1.2.
+#[derive(Debug, Clone)]
+pub struct Mandelbulb {
+    pub power: f64,
+    pub iterations: usize,
+}
+
+impl Mandelbulb {
+    pub fn new(power: f64, iterations: usize) -> Mandelbulb {
+        Mandelbulb { power, iterations }
+    }
+}
+
+impl Default for Mandelbulb {
+    fn default() -> Mandelbulb {
+        Mandelbulb::new(8.0, 12)
+    }
+}
+
+impl Sdf for Mandelbulb {
+    /// Distance estimate from the derivative of the iteration, `0.5 r ln(r) / dr`.
+    fn distance(&self, p: Point3) -> f64 {
+        let mut z = p;
+        let mut dr = 1.0;
+        let mut r = 0.0;
+        for _ in 0..self.iterations {
+            r = z.length();
+            if r > 2.0 {
+                break;
+            }
+            if r == 0.0 {
+                // Stays at the origin, which is inside
+                return -1.0;
+            }
+            // Raises `z` to the power in spherical coordinates
+            let theta = (z.y() / r).acos() * self.power;
+            let phi = z.z().atan2(z.x()) * self.power;
+            dr = r.powf(self.power - 1.0) * self.power * dr + 1.0;
+            let zr = r.powf(self.power);
+            z =
+                zr * Vec3::new(
+                    theta.sin() * phi.cos(),
+                    theta.cos(),
+                    theta.sin() * phi.sin(),
+                ) + p;
+        }
+        0.5 * r.ln() * r / dr
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Union<A: Sdf, B: Sdf> {
+    a: A,
+    b: B,
+}
+
+impl<A: Sdf, B: Sdf> Sdf for Union<A, B> {
+    fn distance(&self, p: Point3) -> f64 {
+        self.a.distance(p).min(self.b.distance(p))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Intersection<A: Sdf, B: Sdf> {
+    a: A,
+    b: B,
+}
+
+impl<A: Sdf, B: Sdf> Sdf for Intersection<A, B> {
+    fn distance(&self, p: Point3) -> f64 {
+        self.a.distance(p).max(self.b.distance(p))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Difference<A: Sdf, B: Sdf> {
+    a: A,
+    b: B,
+}
+
+impl<A: Sdf, B: Sdf> Sdf for Difference<A, B> {
+    fn distance(&self, p: Point3) -> f64 {
+        self.a.distance(p).max(-self.b.distance(p))
+    }
+}
+
+/// Polynomial smooth minimum by Inigo Quilez.
+#[derive(Debug, Clone)]
+pub struct SmoothUnion<A: Sdf, B: Sdf> {
+    a: A,
+    b: B,
+    k: f64,
+}
+
+impl<A: Sdf, B: Sdf> Sdf for SmoothUnion<A, B> {
+    fn distance(&self, p: Point3) -> f64 {
+        let (a, b) = (self.a.distance(p), self.b.distance(p));
+        if self.k <= 0.0 {
+            return a.min(b);
+        }
+        let h = (0.5 + 0.5 * (b - a) / self.k).clamp(0.0, 1.0);
+        b + (a - b) * h - self.k * h * (1.0 - h)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Translate<S: Sdf> {
+    sdf: S,
+    offset: Vec3,
+}
+
+impl<S: Sdf> Sdf for Translate<S> {
+    fn distance(&self, p: Point3) -> f64 {
+        self.sdf.distance(p - self.offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_box() {
+        let round_box = RoundBox::new(Vec3::new(1.0, 2.0, 3.0), 0.5);
+
+        assert!((round_box.distance(Point3::new(2.0, 0.0, 0.0)) - 1.0).abs() < 1e-10);
+        assert!((round_box.distance(Point3::new(0.0, 0.0, 0.0)) + 1.0).abs() < 1e-10);
+        // Beyond a rounded corner, the distance is to the sphere of the corner
+        let corner = Point3::new(0.5, 1.5, 2.5);
+        let p = corner + Vec3::new(1.0, 1.0, 1.0);
+        assert!((round_box.distance(p) - (3.0f64.sqrt() - 0.5)).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_operations() {
+        let a = Sphere::new(1.0);
+        let b = Sphere::new(1.0).translate(Vec3::new(1.5, 0.0, 0.0));
+        let p = Point3::new(0.75, 1.0, 0.0);
+        let (da, db) = (a.distance(p), b.distance(p));
+
+        assert_eq!(da.min(db), a.clone().union(b.clone()).distance(p));
+        assert_eq!(da.max(db), a.clone().intersection(b.clone()).distance(p));
+        assert_eq!(da.max(-db), a.clone().difference(b.clone()).distance(p));
+        // Smoothing fills in the crease between the spheres, and does nothing far from it
+        assert!(a.clone().smooth_union(b.clone(), 0.5).distance(p) < da.min(db));
+        let far = Point3::new(-3.0, 0.0, 0.0);
+        assert_eq!(
+            a.distance(far),
+            a.clone().smooth_union(b, 0.5).distance(far)
+        );
+    }
+
+    #[test]
+    fn test_mandelbulb() {
+        let mandelbulb = Mandelbulb::default();
+
+        assert!(mandelbulb.distance(Point3::new(0.0, 0.0, 0.0)) < 0.0);
+        let d = mandelbulb.distance(Point3::new(0.0, 3.0, 0.0));
+        assert!(0.0 < d && d < 3.0);
+    }
+}
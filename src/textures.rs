@@ -4,9 +4,11 @@ mod noise_texture;
 mod perlin;
 mod solid_color;
 mod texture;
+mod vertex_color_texture;
 
 pub use checker_texture::CheckerTexture;
 pub use image_texture::ImageTexture;
 pub use noise_texture::NoiseTexture;
 pub use solid_color::SolidColor;
 pub use texture::Texture;
+pub use vertex_color_texture::VertexColorTexture;
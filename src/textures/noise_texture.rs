@@ -24,6 +24,12 @@ impl NoiseTexture {
     }
 }
 
+impl Default for NoiseTexture {
+    fn default() -> NoiseTexture {
+        NoiseTexture::new()
+    }
+}
+
 impl Texture for NoiseTexture {
     fn value(&self, _u: f64, _v: f64, p: &Point3) -> Color {
         Color::new(1.0, 1.0, 1.0)
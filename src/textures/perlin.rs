@@ -1,6 +1,9 @@
 use rand::seq::SliceRandom;
 
-use crate::vec3::{Point3, Vec3};
+use crate::{
+    random,
+    vec3::{Point3, Vec3},
+};
 
 const POINT_COUNT: usize = 256;
 
@@ -14,7 +17,7 @@ pub struct Perlin {
 
 impl Perlin {
     pub fn new() -> Perlin {
-        let mut rng = rand::thread_rng();
+        let mut rng = random::rng();
         let mut rand_vec = [Vec3::default(); POINT_COUNT];
         for r in rand_vec.iter_mut() {
             *r = Vec3::random()
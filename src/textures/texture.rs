@@ -1,5 +1,25 @@
-use crate::vec3::{Color, Point3};
+use std::sync::Arc;
+
+use crate::{
+    hittables::Hit,
+    vec3::{Color, Point3},
+};
 
 pub trait Texture: Sync + Send {
     fn value(&self, u: f64, v: f64, p: &Point3) -> Color;
+
+    /// Value at the point of `hit`, for textures that need more than its coordinates.
+    fn value_at(&self, hit: &Hit) -> Color {
+        self.value(hit.u, hit.v, &hit.p)
+    }
+}
+
+impl<T: Texture + ?Sized> Texture for Arc<T> {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color {
+        (**self).value(u, v, p)
+    }
+
+    fn value_at(&self, hit: &Hit) -> Color {
+        (**self).value_at(hit)
+    }
 }
@@ -0,0 +1,35 @@
+use crate::{
+    hittables::Hit,
+    vec3::{Color, Point3},
+};
+
+use super::Texture;
+
+/// Interpolates colors given at the vertices of a `TriangleMesh` across its faces.
+///
+/// `colors` are indexed like the positions of the mesh. Points off a mesh face are black.
+#[derive(Debug, Clone)]
+pub struct VertexColorTexture {
+    colors: Vec<Color>,
+}
+
+impl VertexColorTexture {
+    pub fn new(colors: Vec<Color>) -> VertexColorTexture {
+        VertexColorTexture { colors }
+    }
+}
+
+impl Texture for VertexColorTexture {
+    fn value(&self, _u: f64, _v: f64, _p: &Point3) -> Color {
+        Color::default()
+    }
+
+    fn value_at(&self, hit: &Hit) -> Color {
+        match hit.vertices {
+            Some(([i0, i1, i2], [b0, b1, b2])) => {
+                b0 * self.colors[i0] + b1 * self.colors[i1] + b2 * self.colors[i2]
+            }
+            None => Color::default(),
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use crate::vec3::Color;
+
+/// Operators mapping scene radiance to display values in [0, 1].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToneMappingOperator {
+    /// Clamps each channel to [0, 1]
+    Clamp,
+    /// Reinhard's operator `L / (1 + L)` on the luminance
+    Reinhard,
+    /// Reinhard's operator with a white point, the luminance mapped to 1.
+    ///
+    /// The maximum luminance of the image is used if the white point is `None`.
+    ExtendedReinhard { white_point: Option<f64> },
+    /// Krzysztof Narkowicz's fit of the ACES filmic curve
+    Aces,
+    /// John Hable's filmic curve from Uncharted 2
+    Hable,
+}
+
+/// Converts rendered linear radiance to sRGB encoded colors for 8-bit or 16-bit images.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToneMapping {
+    pub operator: ToneMappingOperator,
+    /// Exposure compensation in stops; the radiance is scaled by `2^exposure`
+    pub exposure: f64,
+}
+
+impl ToneMapping {
+    pub fn new(operator: ToneMappingOperator) -> ToneMapping {
+        ToneMapping {
+            operator,
+            exposure: 0.0,
+        }
+    }
+
+    pub fn new_with_exposure(operator: ToneMappingOperator, exposure: f64) -> ToneMapping {
+        ToneMapping { operator, exposure }
+    }
+
+    /// Applies exposure, the operator and the sRGB transfer function to every pixel.
+    pub fn apply(&self, image: &[Vec<Color>]) -> Vec<Vec<Color>> {
+        let scale = self.exposure.exp2();
+        let white_point = match self.operator {
+            ToneMappingOperator::ExtendedReinhard {
+                white_point: Some(white_point),
+            } => white_point * scale,
+            ToneMappingOperator::ExtendedReinhard { white_point: None } => image
+                .iter()
+                .flatten()
+                .map(|&c| luminance(c * scale))
+                .fold(0.0, f64::max),
+            _ => 0.0,
+        };
+        image
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&c| {
+                        let c = self.map(c * scale, white_point);
+                        Color::new(srgb_oetf(c.x()), srgb_oetf(c.y()), srgb_oetf(c.z()))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Maps exposed linear radiance to linear display values in [0, 1].
+    fn map(&self, c: Color, white_point: f64) -> Color {
+        let c = Color::new(c.x().max(0.0), c.y().max(0.0), c.z().max(0.0));
+        let c = match self.operator {
+            ToneMappingOperator::Clamp => c,
+            ToneMappingOperator::Reinhard => scale_luminance(c, |l| l / (1.0 + l)),
+            ToneMappingOperator::ExtendedReinhard { .. } => scale_luminance(c, |l| {
+                if white_point > 0.0 {
+                    l * (1.0 + l / (white_point * white_point)) / (1.0 + l)
+                } else {
+                    l / (1.0 + l)
+                }
+            }),
+            ToneMappingOperator::Aces => per_channel(c, aces),
+            ToneMappingOperator::Hable => {
+                const WHITE_POINT: f64 = 11.2;
+                const EXPOSURE_BIAS: f64 = 2.0;
+                let white_scale = 1.0 / hable(WHITE_POINT);
+                per_channel(c, |x| hable(EXPOSURE_BIAS * x) * white_scale)
+            }
+        };
+        per_channel(c, |x| x.clamp(0.0, 1.0))
+    }
+}
+
+impl Default for ToneMapping {
+    fn default() -> ToneMapping {
+        ToneMapping::new(ToneMappingOperator::Clamp)
+    }
+}
+
+/// The sRGB opto-electronic transfer function, from linear values in [0, 1] to encoded ones.
+pub fn srgb_oetf(x: f64) -> f64 {
+    if x <= 0.0031308 {
+        12.92 * x
+    } else {
+        1.055 * x.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Relative luminance of linear Rec. 709 primaries
+pub(crate) fn luminance(c: Color) -> f64 {
+    0.2126 * c.x() + 0.7152 * c.y() + 0.0722 * c.z()
+}
+
+/// Scales the color so that its luminance becomes `f(luminance)`, keeping the hue.
+fn scale_luminance(c: Color, f: impl Fn(f64) -> f64) -> Color {
+    let l = luminance(c);
+    if l > 0.0 {
+        c * (f(l) / l)
+    } else {
+        c
+    }
+}
+
+fn per_channel(c: Color, f: impl Fn(f64) -> f64) -> Color {
+    Color::new(f(c.x()), f(c.y()), f(c.z()))
+}
+
+fn aces(x: f64) -> f64 {
+    let (a, b, c, d, e) = (2.51, 0.03, 2.43, 0.59, 0.14);
+    (x * (a * x + b)) / (x * (c * x + d) + e)
+}
+
+fn hable(x: f64) -> f64 {
+    let (a, b, c, d, e, f) = (0.15, 0.50, 0.10, 0.20, 0.02, 0.30);
+    (x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f) - e / f
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gray(x: f64) -> Color {
+        Color::new(x, x, x)
+    }
+
+    fn map(operator: ToneMappingOperator, x: f64) -> f64 {
+        ToneMapping::new(operator).map(gray(x), 0.0).x()
+    }
+
+    #[test]
+    fn test_srgb_oetf() {
+        assert_eq!(0.0, srgb_oetf(0.0));
+        assert!((srgb_oetf(0.002) - 0.02584).abs() < 1e-10);
+        assert!((srgb_oetf(0.18) - 0.46135).abs() < 1e-5);
+        assert!((srgb_oetf(1.0) - 1.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_operators() {
+        assert_eq!(0.5, map(ToneMappingOperator::Clamp, 0.5));
+        assert_eq!(1.0, map(ToneMappingOperator::Clamp, 15.0));
+        assert!((map(ToneMappingOperator::Reinhard, 1.0) - 0.5).abs() < 1e-10);
+        assert!((map(ToneMappingOperator::Reinhard, 15.0) - 15.0 / 16.0).abs() < 1e-10);
+
+        // Bright values are compressed rather than blown out, and the curves are monotonic
+        for operator in [
+            ToneMappingOperator::Reinhard,
+            ToneMappingOperator::Aces,
+            ToneMappingOperator::Hable,
+        ] {
+            let values = [0.0, 0.1, 1.0, 4.0, 15.0].map(|x| map(operator, x));
+            assert_eq!(0.0, values[0].abs());
+            assert!(values.windows(2).all(|w| w[0] < w[1]), "{operator:?}");
+            assert!(values[4] <= 1.0, "{operator:?}");
+        }
+    }
+
+    #[test]
+    fn test_extended_reinhard_white_point() {
+        let tone_mapping = ToneMapping::new(ToneMappingOperator::ExtendedReinhard {
+            white_point: Some(4.0),
+        });
+        let image = tone_mapping.apply(&[vec![gray(4.0), gray(1.0)]]);
+
+        assert!((image[0][0].x() - 1.0).abs() < 1e-10);
+        assert!((image[0][1].x() - srgb_oetf(1.0625 / 2.0)).abs() < 1e-10);
+
+        // Without a white point, the brightest pixel becomes white
+        let tone_mapping =
+            ToneMapping::new(ToneMappingOperator::ExtendedReinhard { white_point: None });
+        let image = tone_mapping.apply(&[vec![gray(2.0), gray(0.5)]]);
+
+        assert!((image[0][0].x() - 1.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_reinhard_keeps_hue() {
+        let c = ToneMapping::new(ToneMappingOperator::Reinhard).map(Color::new(0.8, 0.4, 0.2), 0.0);
+
+        assert!((c.x() / c.y() - 2.0).abs() < 1e-10);
+        assert!((c.y() / c.z() - 2.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_exposure() {
+        let tone_mapping = ToneMapping::new_with_exposure(ToneMappingOperator::Clamp, -2.0);
+        let image = tone_mapping.apply(&[vec![gray(2.0)]]);
+
+        assert!((image[0][0].x() - srgb_oetf(0.5)).abs() < 1e-10);
+    }
+}
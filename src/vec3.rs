@@ -7,6 +7,8 @@ use std::{
 use rand::Rng;
 use rand_distr::{Distribution, UnitBall, UnitDisc};
 
+use crate::random;
+
 #[derive(Debug, Clone, Copy, Default, PartialEq)]
 pub struct Vec3([f64; 3]);
 
@@ -52,7 +54,7 @@ impl Vec3 {
     }
 
     pub fn random() -> Vec3 {
-        let mut rng = rand::thread_rng();
+        let mut rng = random::rng();
         let mut v = [0f64; 3];
         rng.fill(&mut v);
         Vec3(v)
@@ -64,13 +66,13 @@ impl Vec3 {
     }
 
     pub fn random_in_unit_sphere() -> Vec3 {
-        let mut rng = rand::thread_rng();
+        let mut rng = random::rng();
         let v: [f64; 3] = UnitBall.sample(&mut rng);
         Vec3(v)
     }
 
     pub fn random_in_unit_disk() -> Vec3 {
-        let mut rng = rand::thread_rng();
+        let mut rng = random::rng();
         let v: [f64; 2] = UnitDisc.sample(&mut rng);
         Vec3([v[0], v[1], 1.0])
     }
@@ -194,10 +196,11 @@ pub type Point3 = Vec3;
 pub type Color = Vec3;
 
 impl Color {
+    /// Writes an encoded color in [0, 1] as a PPM pixel.
     pub fn write(self, w: &mut impl io::Write) -> io::Result<()> {
-        let r = self.x().sqrt().clamp(0.0, 0.999);
-        let g = self.y().sqrt().clamp(0.0, 0.999);
-        let b = self.z().sqrt().clamp(0.0, 0.999);
+        let r = self.x().clamp(0.0, 0.999);
+        let g = self.y().clamp(0.0, 0.999);
+        let b = self.z().clamp(0.0, 0.999);
 
         // Write the translated [0,255] value of each color component.
         let r = (255.999 * r) as i32;
@@ -390,10 +393,10 @@ mod tests {
     #[test]
     fn test_write_color() -> std::io::Result<()> {
         let mut buf: Vec<u8> = vec![];
-        let color = Color::new(0.25, 0.25, 0.5);
+        let color = Color::new(0.25, 0.5, 1.0);
         color.write(&mut buf)?;
 
-        assert_eq!("127 127 181\n", String::from_utf8_lossy(&buf));
+        assert_eq!("63 127 255\n", String::from_utf8_lossy(&buf));
         Ok(())
     }
 }